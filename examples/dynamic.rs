@@ -1,6 +1,9 @@
 use std::time::Duration;
 
-use muroba::query::{Query, QueryBuilder};
+use muroba::{
+    matcher::{Matcher, PrefixMatcher},
+    query::{Query, QueryBuilder},
+};
 
 fn main() {
     let choice = QueryBuilder::default()
@@ -19,12 +22,12 @@ fn main() {
         .with_prompt("Which langauge is your favorite?")
         .dyn_select(|input: String| {
             if input.is_empty() {
-                LANGUAGES.into_iter().collect()
+                LANGUAGES.iter().collect()
             } else {
                 std::thread::sleep(Duration::from_millis(500));
                 LANGUAGES
-                    .into_iter()
-                    .filter(|lang| lang.starts_with(&input))
+                    .iter()
+                    .filter(|lang| PrefixMatcher.matches(&input, lang).is_some())
                     .collect()
             }
         })
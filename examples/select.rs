@@ -10,10 +10,10 @@ fn main() {
         .unwrap();
     match selected.len() {
         0 => println!("You don't like fruit?"),
-        1 => println!("Your favorite fruit is {}!", selected[0].1),
+        1 => println!("Your favorite fruit is {}!", selected[0]),
         _ => println!(
             "Your favorite fruits are {}!",
-            join_string(selected.iter().map(|(_, choice)| choice.as_str()))
+            join_string(selected.iter().map(|choice| **choice))
         ),
     }
 
@@ -21,9 +21,10 @@ fn main() {
         .with_prompt("Which language is your favorite?")
         .select(LANGUAGES)
         .fix_rows(5)
+        .filterable()
         .show()
         .unwrap();
-    println!("Your favorite language is {}!", selected[0].1);
+    println!("Your favorite language is {}!", selected);
 }
 
 const LANGUAGES: &[&str] = &[
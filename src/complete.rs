@@ -0,0 +1,142 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+    history::History,
+    matcher::{rank, Matcher},
+    Result,
+};
+
+/// Generates completion candidates for the text being typed.
+///
+/// It is run on a separate thread, so it may take a while without blocking the input.
+pub trait Completer: Send + Sync {
+    /// Returns the candidates for `text` whose cursor is at the byte offset `cursor`.
+    fn complete(&self, text: &str, cursor: usize) -> Completion;
+}
+
+impl<F> Completer for F
+where
+    F: Fn(&str, usize) -> Completion + Send + Sync,
+{
+    fn complete(&self, text: &str, cursor: usize) -> Completion {
+        self(text, cursor)
+    }
+}
+
+/// Candidates generated by a [`Completer`].
+pub struct Completion {
+    /// The byte offset where the completed part starts.
+    ///
+    /// A candidate replaces the text between `start` and the cursor.
+    pub start: usize,
+    pub candidates: Vec<String>,
+}
+
+impl Completion {
+    /// Returns the longest common prefix of the candidates.
+    pub fn common_prefix(&self) -> &str {
+        let mut candidates = self.candidates.iter();
+        let first = match candidates.next() {
+            Some(first) => first.as_str(),
+            None => return "",
+        };
+        candidates.fold(first, |prefix, candidate| {
+            let len = prefix
+                .grapheme_indices(true)
+                .zip(candidate.graphemes(true))
+                .find(|((_, a), b)| a != b)
+                .map_or_else(|| prefix.len().min(candidate.len()), |((i, _), _)| i);
+            &prefix[..len]
+        })
+    }
+}
+
+/// Completes the word before the cursor with the candidates which a [`Matcher`] matches,
+/// ranked by score.
+pub struct MatchCompleter<M> {
+    candidates: Vec<String>,
+    matcher: M,
+}
+
+impl<M> MatchCompleter<M> {
+    pub fn new(candidates: impl IntoIterator<Item = impl Into<String>>, matcher: M) -> Self {
+        Self {
+            candidates: candidates.into_iter().map(Into::into).collect(),
+            matcher,
+        }
+    }
+}
+
+impl<M> Completer for MatchCompleter<M>
+where
+    M: Matcher,
+{
+    fn complete(&self, text: &str, cursor: usize) -> Completion {
+        let start = text[..cursor]
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let word = &text[start..cursor];
+        let ranked = rank(
+            &self.matcher,
+            word,
+            self.candidates.iter().map(String::as_str),
+        );
+        Completion {
+            start,
+            candidates: ranked
+                .into_iter()
+                .map(|(index, _)| self.candidates[index].clone())
+                .collect(),
+        }
+    }
+}
+
+/// Suggests how the text being typed may continue.
+pub trait Suggester {
+    /// Returns the most likely text which starts with `text`.
+    fn suggest(&self, text: &str) -> Option<String>;
+}
+
+/// Suggests the first candidate which starts with the text.
+impl<T> Suggester for [T]
+where
+    T: AsRef<str>,
+{
+    fn suggest(&self, text: &str) -> Option<String> {
+        self.iter()
+            .map(AsRef::as_ref)
+            .find(|candidate| candidate.starts_with(text))
+            .map(str::to_string)
+    }
+}
+
+impl<T> Suggester for Vec<T>
+where
+    T: AsRef<str>,
+{
+    fn suggest(&self, text: &str) -> Option<String> {
+        self.as_slice().suggest(text)
+    }
+}
+
+/// Suggests the newest history entry which starts with the text.
+pub struct HistorySuggester {
+    entries: Vec<String>,
+}
+
+impl HistorySuggester {
+    /// Loads the entries stored in `history` under `key`.
+    pub fn new(history: &dyn History, key: &str) -> Result<Self> {
+        let mut entries = history.entries(key)?;
+        entries.reverse();
+        Ok(Self { entries })
+    }
+}
+
+impl Suggester for HistorySuggester {
+    fn suggest(&self, text: &str) -> Option<String> {
+        self.entries.suggest(text)
+    }
+}
@@ -0,0 +1,54 @@
+use std::{error, fmt, io};
+
+/// An error which can occur while a query is shown.
+#[derive(Debug)]
+pub enum Error {
+    /// Reading from or writing to the terminal failed.
+    Io(io::Error),
+    /// The user cancelled the query with Esc or Ctrl-C.
+    Interrupted,
+    /// The query needs an interactive terminal, but there is none,
+    /// and neither an answer on stdin nor a default is given.
+    NotATty,
+    /// The answer read without a terminal is not valid, for the contained reason.
+    Invalid(String),
+    /// The editor of [`EditorQuery`](crate::query::EditorQuery) could not be spawned.
+    Editor(io::Error),
+    /// The list generator of [`DynamicSelectQuery`](crate::query::DynamicSelectQuery) panicked
+    /// with the contained message.
+    ListGenerator(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "terminal I/O failed: {}", e),
+            Error::Interrupted => write!(f, "the query was cancelled"),
+            Error::NotATty => write!(
+                f,
+                "the query needs an interactive terminal or an answer on stdin"
+            ),
+            Error::Invalid(reason) => write!(f, "invalid answer: {}", reason),
+            Error::Editor(e) => write!(f, "failed to spawn the editor: {}", e),
+            Error::ListGenerator(message) => write!(f, "the list generator panicked: {}", message),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io(e) | Error::Editor(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+/// The result type of this crate.
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,103 @@
+use std::{
+    collections::VecDeque,
+    io::{self, stderr, stdin, ErrorKind},
+    time::Duration,
+};
+
+use crossterm::{
+    event::{self, Event, KeyEvent},
+    tty::IsTty,
+};
+
+/// Where a query reads the keys pressed by the user.
+pub trait EventSource {
+    /// Waits for the next event.
+    fn read(&mut self) -> io::Result<Event>;
+    /// Returns `true` if an event can be read without waiting longer than `timeout`.
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool>;
+    /// Returns `true` if the events come from the terminal,
+    /// which is then put in raw mode while a query waits for them.
+    ///
+    /// Otherwise, the events are read only after the pending work of a query is done,
+    /// such as generating a list or a completion, so that a script behaves the same every time.
+    fn is_terminal(&self) -> bool {
+        false
+    }
+    /// Returns `true` if the user can answer the query with the events.
+    ///
+    /// Otherwise, the query reads the answer from stdin without drawing anything.
+    fn is_interactive(&self) -> bool {
+        true
+    }
+}
+
+/// Reads events from the terminal, which is the default source of every query.
+#[derive(Default)]
+pub struct TerminalEvents {
+    is_on_tty: bool,
+}
+
+impl TerminalEvents {
+    /// Creates a source for a query drawn on the controlling terminal,
+    /// which is interactive even if stdin and stderr are redirected.
+    pub fn on_tty() -> Self {
+        Self { is_on_tty: true }
+    }
+}
+
+impl EventSource for TerminalEvents {
+    fn read(&mut self) -> io::Result<Event> {
+        event::read()
+    }
+
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        event::poll(timeout)
+    }
+
+    fn is_terminal(&self) -> bool {
+        true
+    }
+
+    fn is_interactive(&self) -> bool {
+        self.is_on_tty || (stdin().is_tty() && stderr().is_tty())
+    }
+}
+
+/// Replays a script of events, so that a query can be driven without a user.
+///
+/// Reading after the script has ended fails with [`ErrorKind::UnexpectedEof`],
+/// instead of waiting forever.
+pub struct ScriptedEvents {
+    events: VecDeque<Event>,
+}
+
+impl ScriptedEvents {
+    pub fn new(keys: Vec<KeyEvent>) -> Self {
+        Self::from_events(keys.into_iter().map(Event::Key).collect())
+    }
+
+    /// Creates a script which also contains events other than keys, such as pastes.
+    pub fn from_events(events: Vec<Event>) -> Self {
+        Self {
+            events: events.into(),
+        }
+    }
+
+    /// Returns `true` if every event has been read.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+impl EventSource for ScriptedEvents {
+    fn read(&mut self) -> io::Result<Event> {
+        self.events.pop_front().ok_or_else(|| {
+            io::Error::new(ErrorKind::UnexpectedEof, "the scripted events have run out")
+        })
+    }
+
+    /// Always returns `true`, as reading never waits.
+    fn poll(&mut self, _: Duration) -> io::Result<bool> {
+        Ok(true)
+    }
+}
@@ -0,0 +1,257 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use tempfile::NamedTempFile;
+
+use crate::Result;
+
+/// A storage of previous answers, grouped by a key.
+pub trait History {
+    /// Returns the entries stored under `key`, from the oldest to the newest.
+    fn entries(&self, key: &str) -> Result<Vec<String>>;
+    /// Appends an entry under `key`, keeping at most `max_len` entries.
+    ///
+    /// An entry equal to the new one is removed beforehand, so that each entry is stored only once.
+    fn push(&self, key: &str, entry: &str, max_len: usize) -> Result<()>;
+}
+
+fn push_entry(entries: &mut Vec<String>, entry: &str, max_len: usize) {
+    entries.retain(|e| e != entry);
+    entries.push(entry.to_string());
+    if entries.len() > max_len {
+        entries.drain(..entries.len() - max_len);
+    }
+}
+
+/// A history which lives as long as the program runs.
+#[derive(Default)]
+pub struct MemoryHistory {
+    entries: RefCell<HashMap<String, Vec<String>>>,
+}
+
+impl History for MemoryHistory {
+    fn entries(&self, key: &str) -> Result<Vec<String>> {
+        Ok(self.entries.borrow().get(key).cloned().unwrap_or_default())
+    }
+
+    fn push(&self, key: &str, entry: &str, max_len: usize) -> Result<()> {
+        let mut entries = self.entries.borrow_mut();
+        push_entry(entries.entry(key.into()).or_default(), entry, max_len);
+        Ok(())
+    }
+}
+
+/// A history stored in a file.
+///
+/// Each line of the file consists of a key and an entry separated by a tab.
+/// The file is created when the first entry is pushed.
+pub struct FileHistory {
+    path: PathBuf,
+}
+
+impl FileHistory {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    fn load(&self) -> Result<Vec<(String, String)>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(content
+            .lines()
+            .filter_map(|line| {
+                let (key, entry) = line.split_at(line.find('\t')?);
+                Some((unescape(key), unescape(&entry[1..])))
+            })
+            .collect())
+    }
+}
+
+impl History for FileHistory {
+    fn entries(&self, key: &str) -> Result<Vec<String>> {
+        Ok(self
+            .load()?
+            .into_iter()
+            .filter(|(k, _)| k == key)
+            .map(|(_, entry)| entry)
+            .collect())
+    }
+
+    fn push(&self, key: &str, entry: &str, max_len: usize) -> Result<()> {
+        let (entries, others): (Vec<_>, Vec<_>) =
+            self.load()?.into_iter().partition(|(k, _)| k == key);
+        let mut entries = entries.into_iter().map(|(_, entry)| entry).collect();
+        push_entry(&mut entries, entry, max_len);
+
+        // The entries are written to a new file which replaces the old one at once,
+        // so that the history is not lost if writing fails halfway.
+        let dir = match self.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let mut file = io::BufWriter::new(NamedTempFile::new_in(dir)?);
+        for (key, entry) in &others {
+            writeln!(file, "{}\t{}", escape(key), escape(entry))?;
+        }
+        for entry in &entries {
+            writeln!(file, "{}\t{}", escape(key), escape(entry))?;
+        }
+        let file = file.into_inner().map_err(io::IntoInnerError::into_error)?;
+        file.as_file().sync_all()?;
+        file.persist(&self.path).map_err(|e| e.error)?;
+        Ok(())
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn unescape(s: &str) -> String {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('t') => unescaped.push('\t'),
+                Some('n') => unescaped.push('\n'),
+                Some('r') => unescaped.push('\r'),
+                Some(c) => unescaped.push(c),
+                None => {}
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+    unescaped
+}
+
+/// Walks through the history entries of a query.
+pub(crate) struct HistoryBrowser {
+    entries: Vec<String>,
+    /// The index of the entry being shown, which is `entries.len()` for the user's own text.
+    index: usize,
+    draft: String,
+    search: Option<Search>,
+}
+
+struct Search {
+    query: String,
+    /// The index of the matched entry.
+    found: Option<usize>,
+}
+
+impl HistoryBrowser {
+    pub fn new(entries: Vec<String>) -> Self {
+        Self {
+            index: entries.len(),
+            entries,
+            draft: String::new(),
+            search: None,
+        }
+    }
+
+    /// Moves to the previous entry and returns it.
+    ///
+    /// `current` is restored when the user moves past the newest entry.
+    pub fn prev(&mut self, current: &str) -> Option<&str> {
+        if self.index == 0 {
+            return None;
+        }
+        if self.index == self.entries.len() {
+            self.draft = current.to_string();
+        }
+        self.index -= 1;
+        Some(&self.entries[self.index])
+    }
+
+    /// Moves to the next entry and returns it.
+    pub fn next(&mut self) -> Option<&str> {
+        if self.index == self.entries.len() {
+            return None;
+        }
+        self.index += 1;
+        Some(self.entries.get(self.index).unwrap_or(&self.draft))
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.search.is_some()
+    }
+
+    /// Starts a reverse search, or moves to the next older match if already searching.
+    pub fn search(&mut self) {
+        match &mut self.search {
+            Some(search) => {
+                let before = search.found.unwrap_or(self.entries.len());
+                if let Some(found) = find(&self.entries[..before], &search.query) {
+                    search.found = Some(found);
+                }
+            }
+            None => {
+                self.search = Some(Search {
+                    query: String::new(),
+                    found: None,
+                })
+            }
+        }
+    }
+
+    pub fn search_push(&mut self, c: char) {
+        if let Some(search) = &mut self.search {
+            search.query.push(c);
+            let before = search.found.map_or(self.entries.len(), |found| found + 1);
+            search.found = find(&self.entries[..before], &search.query);
+        }
+    }
+
+    pub fn search_pop(&mut self) {
+        if let Some(search) = &mut self.search {
+            search.query.pop();
+            search.found = find(&self.entries, &search.query);
+        }
+    }
+
+    /// Returns the search query and the matched entry with the position of the match.
+    pub fn search_state(&self) -> Option<(&str, Option<(&str, usize)>)> {
+        self.search.as_ref().map(|search| {
+            let found = search.found.map(|found| {
+                let entry = self.entries[found].as_str();
+                (entry, entry.find(&search.query).unwrap_or(0))
+            });
+            (search.query.as_str(), found)
+        })
+    }
+
+    /// Ends the search and returns the matched entry.
+    pub fn finish_search(&mut self, current: &str) -> Option<&str> {
+        let found = self.search.take()?.found?;
+        if self.index == self.entries.len() {
+            self.draft = current.to_string();
+        }
+        self.index = found;
+        Some(&self.entries[found])
+    }
+
+    pub fn cancel_search(&mut self) {
+        self.search = None;
+    }
+}
+
+/// Finds the newest entry which contains `query`.
+fn find(entries: &[String], query: &str) -> Option<usize> {
+    if query.is_empty() {
+        return None;
+    }
+    entries.iter().rposition(|entry| entry.contains(query))
+}
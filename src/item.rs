@@ -18,6 +18,42 @@ pub struct BeginInput;
 /// It is responsible for restoring the console state after [`BeginInput`] is displayed.
 pub struct EndInput;
 
+/// Represents the default answer of an input, which is used when the user enters nothing.
+///
+/// It is shown as a hint right after [`Prompt`].
+pub struct DefaultValue(pub String);
+
+/// Represents a hint about the expected input, which is shown while the input is empty.
+///
+/// It disappears once the user starts typing.
+pub struct Placeholder(pub String);
+
+/// Represents the current mode of a modal text reader, such as [`ViReader`](crate::query::ViReader).
+///
+/// It is shown right before [`BeginInput`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditMode {
+    /// The mode where typed characters are inserted.
+    Insert,
+    /// The mode where keys are interpreted as commands.
+    Normal,
+}
+
+/// Represents the query of a reverse history search.
+///
+/// It is shown right before [`BeginInput`] while the user searches the history.
+pub struct HistorySearch(pub String);
+
+/// Represents the rest of a suggested input, which is shown after the text being typed.
+///
+/// The user can accept the suggestion with Right, End or Ctrl-F.
+pub struct Suggestion(pub String);
+
+/// Represents an error message of an input which failed validation.
+///
+/// It is shown below the input until the user edits it.
+pub struct ValidationError(pub String);
+
 /// Represents possible values of [`ConfirmQuery`].
 ///
 /// It contains a default choice.
@@ -31,6 +67,8 @@ pub struct ListItem {
     pub is_cursor: bool,
     /// `true` if the item is selected.
     pub is_selected: bool,
+    /// The indices of the characters of the item which match the filter, in ascending order.
+    pub matches: Vec<usize>,
 }
 
 pub struct WaitMessage(pub String);
@@ -1,7 +1,13 @@
+pub mod complete;
+mod error;
+pub mod event;
+pub mod history;
 pub mod item;
+pub mod matcher;
 pub mod query;
+pub mod screen;
 pub mod style;
+pub mod terminal;
 pub mod util;
 
-/// The result type of this crate.
-type Result<T> = crossterm::Result<T>;
+pub use error::{Error, Result};
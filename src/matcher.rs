@@ -0,0 +1,238 @@
+use std::{
+    cmp::Reverse,
+    sync::{Mutex, PoisonError},
+};
+
+use regex::Regex;
+
+/// Where a pattern matches a text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    /// How well the pattern matches, where a higher score is ranked first.
+    pub score: i64,
+    /// The indices of the matched characters of the text, in ascending order.
+    pub indices: Vec<usize>,
+}
+
+/// Decides which texts match a pattern typed by the user, and how well.
+pub trait Matcher: Send + Sync {
+    /// Matches `pattern` against `text`, or returns `None` if it does not match.
+    ///
+    /// An empty pattern should match every text.
+    fn matches(&self, pattern: &str, text: &str) -> Option<Match>;
+}
+
+impl<F> Matcher for F
+where
+    F: Fn(&str, &str) -> Option<Match> + Send + Sync,
+{
+    fn matches(&self, pattern: &str, text: &str) -> Option<Match> {
+        self(pattern, text)
+    }
+}
+
+/// Returns the texts which `matcher` matches with `pattern`, from the best match,
+/// each with its index in `texts`.
+///
+/// Texts with the same score stay in the order of `texts`.
+pub fn rank<'t>(
+    matcher: &dyn Matcher,
+    pattern: &str,
+    texts: impl IntoIterator<Item = &'t str>,
+) -> Vec<(usize, Match)> {
+    let mut ranked: Vec<_> = texts
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, text)| Some((index, matcher.matches(pattern, text)?)))
+        .collect();
+    // The sort is stable, so it keeps the order of the texts with the same score.
+    ranked.sort_by_key(|(_, found)| Reverse(found.score));
+    ranked
+}
+
+/// Matches texts which start with the pattern, where shorter texts are ranked first.
+pub struct PrefixMatcher;
+
+impl Matcher for PrefixMatcher {
+    fn matches(&self, pattern: &str, text: &str) -> Option<Match> {
+        let rest = text.strip_prefix(pattern)?;
+        Some(Match {
+            score: -(rest.chars().count() as i64),
+            indices: (0..pattern.chars().count()).collect(),
+        })
+    }
+}
+
+/// Matches texts which contain the pattern, ignoring the case.
+///
+/// Texts where the pattern starts a word, or appears earlier, are ranked first.
+pub struct SubstringMatcher;
+
+impl Matcher for SubstringMatcher {
+    fn matches(&self, pattern: &str, text: &str) -> Option<Match> {
+        let pattern: Vec<char> = pattern.chars().map(to_lowercase).collect();
+        let chars: Vec<char> = text.chars().collect();
+        let lowercase: Vec<char> = chars.iter().copied().map(to_lowercase).collect();
+        let start = (0..=chars.len().checked_sub(pattern.len())?)
+            .find(|&start| lowercase[start..].starts_with(&pattern))?;
+        let mut score = -(start as i64);
+        if is_boundary(&chars, start) {
+            score += BONUS_BOUNDARY;
+        }
+        Some(Match {
+            score,
+            indices: (start..start + pattern.len()).collect(),
+        })
+    }
+}
+
+/// Matches texts which contain the characters of the pattern in order, like fzf.
+///
+/// A match scores higher when the characters are consecutive or start words.
+/// The case is ignored unless the pattern contains an uppercase character.
+pub struct FuzzyMatcher;
+
+impl Matcher for FuzzyMatcher {
+    fn matches(&self, pattern: &str, text: &str) -> Option<Match> {
+        let is_case_sensitive = pattern.chars().any(char::is_uppercase);
+        let normalize = |c: char| {
+            if is_case_sensitive {
+                c
+            } else {
+                to_lowercase(c)
+            }
+        };
+        let pattern: Vec<char> = pattern.chars().map(normalize).collect();
+        let chars: Vec<char> = text.chars().collect();
+        if pattern.is_empty() {
+            return Some(Match {
+                score: 0,
+                indices: vec![],
+            });
+        }
+
+        // Finds where the first match ends, and then the shortest match which ends there.
+        let mut p = 0;
+        let mut end = None;
+        for (i, &c) in chars.iter().enumerate() {
+            if normalize(c) == pattern[p] {
+                p += 1;
+                if p == pattern.len() {
+                    end = Some(i);
+                    break;
+                }
+            }
+        }
+        let end = end?;
+        let mut indices = Vec::with_capacity(pattern.len());
+        let mut p = pattern.len();
+        for i in (0..=end).rev() {
+            if normalize(chars[i]) == pattern[p - 1] {
+                indices.push(i);
+                p -= 1;
+                if p == 0 {
+                    break;
+                }
+            }
+        }
+        indices.reverse();
+
+        Some(Match {
+            score: fuzzy_score(&chars, &indices),
+            indices,
+        })
+    }
+}
+
+/// Matches texts where the pattern, as a regular expression, finds a match.
+///
+/// Texts where the match appears earlier are ranked first.
+/// A pattern which is not a valid regular expression, such as one still being typed,
+/// is matched literally.
+#[derive(Default)]
+pub struct RegexMatcher {
+    /// The last pattern with its compiled regular expression.
+    cache: Mutex<Option<(String, Regex)>>,
+}
+
+impl RegexMatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Matcher for RegexMatcher {
+    fn matches(&self, pattern: &str, text: &str) -> Option<Match> {
+        let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+        let regex = match &*cache {
+            Some((cached, regex)) if cached == pattern => regex,
+            _ => {
+                let regex = Regex::new(pattern)
+                    .or_else(|_| Regex::new(&regex::escape(pattern)))
+                    .ok()?;
+                &cache.insert((pattern.to_string(), regex)).1
+            }
+        };
+        let found = regex.find(text)?;
+        let mut start = 0;
+        let mut indices = vec![];
+        for (i, (offset, _)) in text.char_indices().enumerate() {
+            if offset < found.start() {
+                start = i + 1;
+            } else if offset < found.end() {
+                indices.push(i);
+            }
+        }
+        Some(Match {
+            score: -(start as i64),
+            indices,
+        })
+    }
+}
+
+const SCORE_MATCH: i64 = 16;
+const BONUS_BOUNDARY: i64 = 8;
+const BONUS_CONSECUTIVE: i64 = 4;
+const BONUS_FIRST_CHAR: i64 = 2;
+const PENALTY_GAP_START: i64 = 3;
+const PENALTY_GAP_EXTENSION: i64 = 1;
+
+fn fuzzy_score(chars: &[char], indices: &[usize]) -> i64 {
+    let mut score = 0;
+    let mut prev: Option<usize> = None;
+    for &i in indices {
+        score += SCORE_MATCH;
+        if is_boundary(chars, i) {
+            score += BONUS_BOUNDARY;
+        }
+        match prev {
+            Some(prev) if prev + 1 == i => score += BONUS_CONSECUTIVE,
+            Some(prev) => {
+                let gap = (i - prev - 1) as i64;
+                score -= PENALTY_GAP_START + (gap - 1) * PENALTY_GAP_EXTENSION;
+            }
+            None if i == 0 => score += BONUS_FIRST_CHAR,
+            None => {}
+        }
+        prev = Some(i);
+    }
+    score
+}
+
+/// Returns `true` if the character at `i` starts a word.
+fn is_boundary(chars: &[char], i: usize) -> bool {
+    match i.checked_sub(1).map(|prev| chars[prev]) {
+        None => true,
+        Some(prev) => {
+            let c = chars[i];
+            (!prev.is_alphanumeric() && c.is_alphanumeric())
+                || (prev.is_lowercase() && c.is_uppercase())
+                || (!prev.is_numeric() && c.is_numeric())
+        }
+    }
+}
+
+/// Lowercases a character, keeping it a single character so that indices do not shift.
+fn to_lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
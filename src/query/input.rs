@@ -1,29 +1,53 @@
 use std::{
+    cell::Cell,
     ffi::OsString,
-    io::{Read, Write},
+    fmt::Display,
+    io::{self, Read, Write},
+    marker::PhantomData,
     path::Path,
     process,
+    rc::Rc,
+    str::FromStr,
+    sync::{
+        mpsc::{sync_channel, Receiver, TryRecvError},
+        Arc,
+    },
+    thread,
+    time::Duration,
 };
 
 use crossterm::{
     cursor::{self, Hide, Show},
-    event::{self, Event},
+    event::{self, Event, KeyEvent, KeyEventKind},
     queue,
-    terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType},
+    style::Print,
+    terminal::{Clear, ClearType},
 };
-use cursor::MoveToColumn;
-use event::KeyCode;
+use cursor::{MoveToColumn, MoveToPreviousLine};
+use event::{KeyCode, KeyModifiers};
 use process::Command;
+use unicode_width::UnicodeWidthStr;
 
 use crate::style::Styler;
-use crate::Result;
 use crate::{
-    item::{BeginInput, ConfirmChoice, EndInput, Overflow, Prompt},
-    util::trim_print,
+    complete::{Completer, Completion, Suggester},
+    event::EventSource,
+    history::{History, HistoryBrowser},
+    item::{
+        BeginInput, ConfirmChoice, DefaultValue, EditMode, EndInput, HistorySearch, ListItem,
+        Overflow, Placeholder, Prompt, Suggestion, ValidationError,
+    },
+    terminal::{Backend, TerminalGuard},
+    util::{rewind, trim_print, trim_print_with_cursor, wrap},
 };
+use crate::{Error, Result};
 
 use super::{
-    reader::{CharacterShield, EmptyShield, PlainReader, SecretReader, TextReader},
+    is_cancel_key, read_answer,
+    reader::{
+        CharacterShield, ClearTextReader, EmptyShield, MultiLineReader, PlainReader, SecretReader,
+        TextReader, ViReader,
+    },
     Query, QueryBuilder,
 };
 
@@ -31,78 +55,645 @@ pub struct InputQuery<'a, S, R> {
     prompt: Prompt,
     style: &'a S,
     reader: R,
+    history: Option<(&'a dyn History, String)>,
+    history_len: usize,
+    completer: Option<Arc<dyn Completer>>,
+    suggester: Option<Box<dyn Suggester + 'a>>,
+    validator: Option<Validator<'a>>,
+    default: Option<String>,
+    placeholder: Option<String>,
+}
+
+type Validator<'a> = Box<dyn Fn(&str) -> std::result::Result<(), String> + 'a>;
+
+const DEFAULT_HISTORY_LEN: usize = 100;
+const MAX_CANDIDATE_ROWS: usize = 8;
+
+impl<'a, S, R> InputQuery<'a, S, R> {
+    fn new(prompt: Prompt, style: &'a S, reader: R) -> Self {
+        Self {
+            prompt,
+            style,
+            reader,
+            history: None,
+            history_len: DEFAULT_HISTORY_LEN,
+            completer: None,
+            suggester: None,
+            validator: None,
+            default: None,
+            placeholder: None,
+        }
+    }
+
+    fn with_reader<T>(self, reader: T) -> InputQuery<'a, S, T> {
+        InputQuery {
+            prompt: self.prompt,
+            style: self.style,
+            reader,
+            history: self.history,
+            history_len: self.history_len,
+            completer: self.completer,
+            suggester: self.suggester,
+            validator: self.validator,
+            default: self.default,
+            placeholder: self.placeholder,
+        }
+    }
+
+    /// Validates the input when Enter is pressed.
+    ///
+    /// If the validator returns an error, the query keeps asking
+    /// and shows the error message until the user edits the input.
+    pub fn with_validator(
+        self,
+        validator: impl Fn(&str) -> std::result::Result<(), String> + 'a,
+    ) -> Self {
+        Self {
+            validator: Some(Box::new(validator)),
+            ..self
+        }
+    }
+
+    /// Sets the text shown while the input is empty, which is never returned as the answer.
+    pub fn with_placeholder(self, placeholder: impl Into<String>) -> Self {
+        Self {
+            placeholder: Some(placeholder.into()),
+            ..self
+        }
+    }
+}
+
+// A secret input cannot use these, as they would store or show the secret.
+impl<'a, S, R> InputQuery<'a, S, R>
+where
+    R: ClearTextReader,
+{
+    /// Sets the answer for an empty input, which is shown as a hint.
+    pub fn with_default(self, default: impl Into<String>) -> Self {
+        Self {
+            default: Some(default.into()),
+            ..self
+        }
+    }
+
+    /// Stores the answer in `history` under `key`.
+    ///
+    /// The previous answers can be recalled with Up and Down,
+    /// or searched with Ctrl-R.
+    pub fn with_history(self, history: &'a dyn History, key: impl Into<String>) -> Self {
+        Self {
+            history: Some((history, key.into())),
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of answers kept in the history.
+    pub fn history_len(self, history_len: usize) -> Self {
+        Self {
+            history_len,
+            ..self
+        }
+    }
+
+    /// Completes the text with Tab.
+    ///
+    /// The first Tab completes the longest common prefix of the candidates,
+    /// and pressing Tab again shows the candidates.
+    /// The candidates are shown at once if the prefix does not extend the text,
+    /// as candidates matched fuzzily may not start with it.
+    pub fn with_completer(self, completer: impl Completer + 'static) -> Self {
+        Self {
+            completer: Some(Arc::new(completer)),
+            ..self
+        }
+    }
+
+    /// Shows a suggestion after the text being typed, which can be accepted
+    /// with Right, End or Ctrl-F.
+    pub fn with_suggester(self, suggester: impl Suggester + 'a) -> Self {
+        Self {
+            suggester: Some(Box::new(suggester)),
+            ..self
+        }
+    }
 }
 
 impl<'a, S, R> Query for InputQuery<'a, S, R>
 where
-    S: Styler<Prompt> + Styler<BeginInput> + Styler<EndInput> + Styler<Overflow>,
+    S: Styler<Prompt>
+        + Styler<DefaultValue>
+        + Styler<EditMode>
+        + Styler<HistorySearch>
+        + Styler<BeginInput>
+        + Styler<EndInput>
+        + Styler<Placeholder>
+        + Styler<Suggestion>
+        + Styler<ValidationError>
+        + Styler<ListItem>
+        + Styler<Overflow>,
     R: TextReader,
 {
     type Result = String;
 
-    fn show_on(self, f: &mut impl Write) -> Result<Self::Result> {
+    fn show_with(
+        self,
+        f: &mut impl Backend,
+        events: &mut impl EventSource,
+    ) -> Result<Self::Result> {
         let Self {
             prompt,
             style,
             mut reader,
+            history,
+            history_len,
+            completer,
+            suggester,
+            validator,
+            default,
+            placeholder,
         } = self;
 
+        if !events.is_interactive() {
+            let answer = match (read_answer()?, default) {
+                (Some(answer), Some(default)) if answer.is_empty() => default,
+                (Some(answer), _) => answer,
+                (None, Some(default)) => default,
+                (None, None) => return Err(Error::NotATty),
+            };
+            if let Some(Err(message)) = validator.as_ref().map(|v| v(&answer)) {
+                return Err(Error::Invalid(message));
+            }
+            return Ok(answer);
+        }
+        let f = &mut TerminalGuard::new(f, events.is_terminal());
+
+        let mut browser = match &history {
+            Some((history, key)) => Some(HistoryBrowser::new(history.entries(key)?)),
+            None => None,
+        };
+        let mut candidates = vec![];
+        // The receiver of the completion being generated, and whether it was requested by a repeated Tab.
+        let mut pending_completion: Option<(Receiver<Completion>, bool)> = None;
+        let mut is_last_tab = false;
+        // Suggests only when the cursor is at the end, where the suggestion is shown.
+        let suggest = |reader: &R| {
+            let text = reader.text();
+            if text.is_empty() || reader.cursor() < text.len() {
+                return None;
+            }
+            suggester
+                .as_ref()?
+                .suggest(text)
+                .filter(|suggestion| suggestion.len() > text.len() && suggestion.starts_with(text))
+        };
+        let mut suggestion = suggest(&reader);
+        let mut error = None;
+        let mut is_cancelled = false;
+
         style.style(f, &prompt)?;
-        let (x, _) = cursor::position()?;
-        style.style(f, &BeginInput)?;
-        enable_raw_mode()?;
-        let result = loop {
-            if let Event::Key(event) = event::read()? {
-                let redraw = match event.code {
-                    KeyCode::Enter => break reader.get_result(),
-                    _ => reader.on_key(&event),
+        let (x, _) = f.cursor_position()?;
+        let decoration = Decoration {
+            default: default.as_deref(),
+            placeholder: placeholder.as_deref(),
+            suggestion: suggestion.as_deref(),
+            ..Default::default()
+        };
+        draw_input(style, f, x, &reader, &decoration, false)?;
+        f.enable_bracketed_paste()?;
+        f.flush()?;
+        f.enable_raw_mode()?;
+        loop {
+            if let Some((rx, is_repeated)) = &pending_completion {
+                const POLL_DURATION: Duration = Duration::from_millis(10);
+                match rx.try_recv() {
+                    Ok(completion) => {
+                        let is_repeated = *is_repeated;
+                        pending_completion = None;
+                        let cursor = reader.cursor();
+                        let word = match reader.text().get(completion.start..cursor) {
+                            Some(word) => word.to_string(),
+                            None => continue,
+                        };
+                        // A single candidate is the completion itself, but a common prefix
+                        // is used only if it extends the word, as a candidate from a fuzzy
+                        // match may not contain the word as it is.
+                        let completed = match completion.candidates.as_slice() {
+                            [] => continue,
+                            [candidate] if *candidate != word => Some(candidate.as_str()),
+                            _ => Some(completion.common_prefix())
+                                .filter(|prefix| prefix.len() > word.len())
+                                .filter(|prefix| prefix.starts_with(word.as_str())),
+                        };
+                        if let Some(completed) = completed {
+                            reader.replace_range(completion.start..cursor, completed);
+                        } else if is_repeated || !completion.common_prefix().starts_with(&word) {
+                            candidates = completion.candidates;
+                        } else {
+                            continue;
+                        }
+                        suggestion = suggest(&reader);
+                        f.disable_raw_mode()?;
+                        let decoration = Decoration {
+                            candidates: &candidates,
+                            default: default.as_deref(),
+                            placeholder: placeholder.as_deref(),
+                            suggestion: suggestion.as_deref(),
+                            error: error.as_deref(),
+                            ..Default::default()
+                        };
+                        draw_input(style, f, x, &reader, &decoration, false)?;
+                        f.flush()?;
+                        f.enable_raw_mode()?;
+                        continue;
+                    }
+                    Err(TryRecvError::Empty) => {
+                        if !events.is_terminal() {
+                            thread::sleep(POLL_DURATION);
+                            continue;
+                        }
+                        if !events.poll(POLL_DURATION)? {
+                            continue;
+                        }
+                    }
+                    Err(TryRecvError::Disconnected) => pending_completion = None,
+                }
+            }
+
+            let (event, pasted) = match events.read()? {
+                Event::Key(event) if event.kind != KeyEventKind::Release => (event, None),
+                // A paste is handled like a key which is not bound to anything else.
+                Event::Paste(text) => (KeyEvent::from(KeyCode::Null), Some(text)),
+                _ => continue,
+            };
+            // A key pressed while completing discards the completion.
+            pending_completion = None;
+            let old_input = error.is_some().then(|| reader.input().to_string());
+            let is_tab = event.code == KeyCode::Tab;
+            let mut redraw = !is_tab && !candidates.is_empty();
+            if !is_tab {
+                candidates.clear();
+            }
+
+            if let Some(browser) = browser.as_mut().filter(|b| b.is_searching()) {
+                let is_handled = match (event.code, event.modifiers) {
+                    (KeyCode::Char('r'), KeyModifiers::CONTROL) => {
+                        browser.search();
+                        true
+                    }
+                    (KeyCode::Char('g'), KeyModifiers::CONTROL) | (KeyCode::Esc, _) => {
+                        browser.cancel_search();
+                        true
+                    }
+                    (KeyCode::Backspace, _) => {
+                        browser.search_pop();
+                        true
+                    }
+                    (KeyCode::Char(c), modifiers)
+                        if (modifiers - KeyModifiers::SHIFT).is_empty() =>
+                    {
+                        browser.search_push(c);
+                        true
+                    }
+                    _ => {
+                        // Any other key accepts the match and is handled as usual.
+                        if let Some(entry) = browser.finish_search(reader.text()) {
+                            reader.set_text(entry.to_string());
+                        }
+                        redraw = true;
+                        false
+                    }
                 };
-                if redraw {
-                    disable_raw_mode()?;
-                    queue!(f, Hide, MoveToColumn(x + 1))?;
-                    style.style(f, &BeginInput)?;
-                    trim_print(style, f, reader.text())?;
-                    queue!(f, Show, Clear(ClearType::UntilNewLine))?;
+                if is_handled {
+                    f.disable_raw_mode()?;
+                    let decoration = Decoration {
+                        default: default.as_deref(),
+                        placeholder: placeholder.as_deref(),
+                        search: browser.search_state(),
+                        error: error.as_deref(),
+                        ..Default::default()
+                    };
+                    draw_input(style, f, x, &reader, &decoration, false)?;
                     f.flush()?;
-                    enable_raw_mode()?;
+                    f.enable_raw_mode()?;
+                    continue;
                 }
             }
-        };
-        disable_raw_mode()?;
+
+            redraw |= match (event.code, event.modifiers, &mut browser) {
+                _ if is_cancel_key(&event, false) => {
+                    is_cancelled = true;
+                    break;
+                }
+                (KeyCode::Enter, _, _) => {
+                    // The default becomes the text only once it is valid,
+                    // so that the user is not left editing a rejected default.
+                    let default = default.as_ref().filter(|_| reader.input().is_empty());
+                    let answer = default.map_or(reader.input(), String::as_str);
+                    match validator.as_ref().map(|v| v(answer)) {
+                        Some(Err(message)) => {
+                            error = Some(message);
+                            true
+                        }
+                        _ => {
+                            if let Some(default) = default {
+                                reader.set_text(default.clone());
+                            }
+                            break;
+                        }
+                    }
+                }
+                (KeyCode::Tab, _, _) if completer.is_some() => {
+                    let (tx, rx) = sync_channel(1);
+                    let completer = completer.clone().unwrap();
+                    let text = reader.text().to_string();
+                    let cursor = reader.cursor();
+                    thread::spawn(move || {
+                        // The query might not wait for the completion anymore.
+                        let _ = tx.send(completer.complete(&text, cursor));
+                    });
+                    pending_completion = Some((rx, is_last_tab));
+                    false
+                }
+                (KeyCode::Right, _, _)
+                | (KeyCode::End, _, _)
+                | (KeyCode::Char('f'), KeyModifiers::CONTROL, _)
+                    if suggestion.is_some() =>
+                {
+                    reader.set_text(suggestion.take().unwrap());
+                    true
+                }
+                (KeyCode::Up, _, Some(browser)) => match browser.prev(reader.text()) {
+                    Some(entry) => {
+                        reader.set_text(entry.to_string());
+                        true
+                    }
+                    None => false,
+                },
+                (KeyCode::Down, _, Some(browser)) => match browser.next() {
+                    Some(entry) => {
+                        reader.set_text(entry.to_string());
+                        true
+                    }
+                    None => false,
+                },
+                (KeyCode::Char('r'), KeyModifiers::CONTROL, Some(browser)) => {
+                    browser.search();
+                    true
+                }
+                _ => match &pasted {
+                    Some(text) => reader.on_paste(text),
+                    None => {
+                        // Esc cancels only if the reader does not use it.
+                        if is_cancel_key(&event, !reader.handles_esc()) {
+                            is_cancelled = true;
+                            break;
+                        }
+                        reader.on_key(&event)
+                    }
+                },
+            };
+            is_last_tab = is_tab;
+            if matches!(&old_input, Some(old_input) if old_input != reader.input()) {
+                error = None;
+                redraw = true;
+            }
+            if redraw {
+                let search = browser.as_ref().and_then(HistoryBrowser::search_state);
+                suggestion = search.is_none().then(|| suggest(&reader)).flatten();
+                f.disable_raw_mode()?;
+                let decoration = Decoration {
+                    default: default.as_deref(),
+                    placeholder: placeholder.as_deref(),
+                    search,
+                    candidates: &candidates,
+                    suggestion: suggestion.as_deref(),
+                    error: error.as_deref(),
+                };
+                draw_input(style, f, x, &reader, &decoration, false)?;
+                f.flush()?;
+                f.enable_raw_mode()?;
+            }
+        }
+        f.disable_raw_mode()?;
+        f.disable_bracketed_paste()?;
+        draw_input(style, f, x, &reader, &Decoration::default(), true)?;
         style.style(f, &EndInput)?;
         writeln!(f)?;
+        if is_cancelled {
+            return Err(Error::Interrupted);
+        }
 
+        let result = reader.get_result();
+        if let Some((history, key)) = history {
+            if !result.is_empty() {
+                history.push(&key, &result, history_len)?;
+            }
+        }
         Ok(result)
     }
 }
 
+/// What is drawn along with the user input.
+#[derive(Default)]
+struct Decoration<'d> {
+    /// The default answer, which is drawn as a hint before the input.
+    default: Option<&'d str>,
+    /// The text drawn while the input is empty.
+    placeholder: Option<&'d str>,
+    /// The search query and the matched entry, which is drawn instead of the input.
+    search: Option<(&'d str, Option<(&'d str, usize)>)>,
+    /// The completion candidates drawn below the input.
+    candidates: &'d [String],
+    /// The suggested text, which is drawn after the input.
+    suggestion: Option<&'d str>,
+    /// The validation error drawn below the input.
+    error: Option<&'d str>,
+}
+
+/// Draws the user input after the prompt, which ends at column `x`.
+///
+/// If `is_done` is `false`, the terminal cursor is placed where the text is being edited.
+/// Otherwise, only the text is drawn and the decoration is cleared.
+fn draw_input<S>(
+    style: &S,
+    f: &mut impl Backend,
+    x: u16,
+    reader: &impl TextReader,
+    decoration: &Decoration,
+    is_done: bool,
+) -> io::Result<()>
+where
+    S: Styler<DefaultValue>
+        + Styler<EditMode>
+        + Styler<HistorySearch>
+        + Styler<BeginInput>
+        + Styler<Placeholder>
+        + Styler<Suggestion>
+        + Styler<ValidationError>
+        + Styler<ListItem>
+        + Styler<Overflow>,
+{
+    queue!(f, Hide, MoveToColumn(x))?;
+    if is_done {
+        style.style(f, &BeginInput)?;
+        trim_print(style, f, reader.text())?;
+        return queue!(f, Clear(ClearType::FromCursorDown), Show);
+    }
+
+    if let Some(default) = decoration.default {
+        style.style(f, &DefaultValue(default.to_string()))?;
+    }
+    let (text, at) = if let Some((query, found)) = decoration.search {
+        style.style(f, &HistorySearch(query.to_string()))?;
+        found.unwrap_or(("", 0))
+    } else {
+        if let Some(mode) = reader.edit_mode() {
+            style.style(f, &mode)?;
+        }
+        (reader.text(), reader.cursor())
+    };
+    style.style(f, &BeginInput)?;
+    let column = trim_print_with_cursor(style, f, text, at)?;
+    if let Some(placeholder) = decoration.placeholder {
+        if decoration.search.is_none() && reader.input().is_empty() {
+            style.style(f, &Placeholder(placeholder.to_string()))?;
+        }
+    }
+    if let Some(suggestion) = decoration.suggestion {
+        style.style(f, &Suggestion(suggestion[text.len()..].to_string()))?;
+    }
+    queue!(f, Clear(ClearType::FromCursorDown))?;
+
+    let mut rows = 0;
+    if let Some(error) = decoration.error {
+        writeln!(f)?;
+        style.style(f, &ValidationError(error.to_string()))?;
+        rows += 1;
+    }
+    for candidate in decoration.candidates.iter().take(MAX_CANDIDATE_ROWS) {
+        writeln!(f)?;
+        style.style(
+            f,
+            &ListItem {
+                item: candidate.clone(),
+                is_cursor: false,
+                is_selected: false,
+                matches: vec![],
+            },
+        )?;
+        rows += 1;
+    }
+    if rows > 0 {
+        queue!(f, MoveToPreviousLine(rows))?;
+    }
+    queue!(f, MoveToColumn(column), Show)
+}
+
 impl<'a, S> QueryBuilder<'a, S>
 where
     S: Styler<Prompt> + Styler<BeginInput> + Styler<EndInput>,
 {
     pub fn input(self) -> InputQuery<'a, S, PlainReader> {
-        InputQuery {
-            prompt: Prompt(self.prompt.unwrap_or_default()),
-            style: self.style,
-            reader: PlainReader::default(),
-        }
+        InputQuery::new(
+            Prompt(self.prompt.unwrap_or_default()),
+            self.style,
+            PlainReader::default(),
+        )
     }
 
     pub fn secret(self) -> InputQuery<'a, S, SecretReader<EmptyShield>> {
-        InputQuery {
-            prompt: Prompt(self.prompt.unwrap_or_default()),
-            style: self.style,
-            reader: SecretReader::new(EmptyShield),
-        }
+        InputQuery::new(
+            Prompt(self.prompt.unwrap_or_default()),
+            self.style,
+            SecretReader::new(EmptyShield),
+        )
+    }
+
+    /// Asks for a text which is parsed into `T`.
+    pub fn parse<T>(self) -> ParseQuery<'a, S, PlainReader, T>
+    where
+        T: FromStr,
+    {
+        self.input().parse()
+    }
+}
+
+impl<'a, S> InputQuery<'a, S, PlainReader> {
+    /// Starts with `text` in the input, which the user can edit.
+    pub fn with_initial_text(self, text: impl Into<String>) -> Self {
+        self.with_reader(PlainReader::with_text(text))
+    }
+
+    /// Uses vi-like modal editing instead of the default key bindings.
+    pub fn vi_mode(self) -> InputQuery<'a, S, ViReader> {
+        let text = self.reader.text().to_string();
+        self.with_reader(ViReader::with_text(text))
     }
 }
 
 impl<'a, S> InputQuery<'a, S, SecretReader<EmptyShield>> {
     pub fn with_replace_char(self, c: char) -> InputQuery<'a, S, SecretReader<CharacterShield>> {
-        InputQuery {
-            prompt: self.prompt,
-            style: self.style,
-            reader: SecretReader::new(CharacterShield::new(c)),
+        self.with_reader(SecretReader::new(CharacterShield::new(c)))
+    }
+}
+
+/// A query which parses the input into `T`.
+///
+/// It is created by [`InputQuery::parse`]. The parse error is shown
+/// like a validation error until the input can be parsed.
+pub struct ParseQuery<'a, S, R, T> {
+    input: InputQuery<'a, S, R>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, S, R> InputQuery<'a, S, R> {
+    /// Parses the input into `T` instead of returning a [`String`].
+    pub fn parse<T>(self) -> ParseQuery<'a, S, R, T>
+    where
+        T: FromStr,
+    {
+        ParseQuery {
+            input: self,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, S, R, T> Query for ParseQuery<'a, S, R, T>
+where
+    InputQuery<'a, S, R>: Query<Result = String>,
+    T: FromStr + 'a,
+    T::Err: Display,
+{
+    type Result = T;
+
+    fn show_with(
+        self,
+        f: &mut impl Backend,
+        events: &mut impl EventSource,
+    ) -> Result<Self::Result> {
+        let mut input = self.input;
+        let validator = input.validator.take();
+        // The value parsed while validating the answer is returned as it is.
+        let parsed = Rc::new(Cell::new(None));
+        let input = input.with_validator({
+            let parsed = parsed.clone();
+            move |text| {
+                if let Some(validator) = &validator {
+                    validator(text)?;
+                }
+                let value = text.parse::<T>().map_err(|e| e.to_string())?;
+                parsed.set(Some(value));
+                Ok(())
+            }
+        });
+
+        let answer = input.show_with(f, events)?;
+        match parsed.take() {
+            Some(value) => Ok(value),
+            None => answer
+                .parse()
+                .map_err(|e: T::Err| Error::Invalid(e.to_string())),
         }
     }
 }
@@ -119,36 +710,64 @@ where
 {
     type Result = bool;
 
-    fn show_on(self, f: &mut impl Write) -> Result<Self::Result> {
+    fn show_with(
+        self,
+        f: &mut impl Backend,
+        events: &mut impl EventSource,
+    ) -> Result<Self::Result> {
         let Self {
             prompt,
             style,
             default,
         } = self;
 
+        if !events.is_interactive() {
+            let answer = read_answer()?.map(|answer| answer.trim().to_lowercase());
+            return match (answer.as_deref(), default) {
+                (Some("y") | Some("yes"), _) => Ok(true),
+                (Some("n") | Some("no"), _) => Ok(false),
+                (None | Some(""), Some(default)) => Ok(default),
+                (None | Some(""), None) => Err(Error::NotATty),
+                (Some(answer), _) => Err(Error::Invalid(format!(
+                    "expected y or n, but got `{}`",
+                    answer
+                ))),
+            };
+        }
+        let f = &mut TerminalGuard::new(f, events.is_terminal());
+
         style.style(f, &prompt)?;
         style.style(f, &ConfirmChoice(default))?;
         style.style(f, &BeginInput)?;
 
-        enable_raw_mode()?;
+        f.enable_raw_mode()?;
         let is_yes = loop {
-            if let Event::Key(event) = event::read()? {
+            if let Event::Key(event) = events.read()? {
+                if event.kind == KeyEventKind::Release {
+                    continue;
+                }
+                if is_cancel_key(&event, true) {
+                    f.disable_raw_mode()?;
+                    style.style(f, &EndInput)?;
+                    writeln!(f)?;
+                    return Err(Error::Interrupted);
+                }
                 match event.code {
                     KeyCode::Char(c @ 'y') | KeyCode::Char(c @ 'Y') => {
-                        disable_raw_mode()?;
-                        println!("{}", c);
+                        f.disable_raw_mode()?;
+                        queue!(f, Print(c))?;
                         break true;
                     }
                     KeyCode::Char(c @ 'n') | KeyCode::Char(c @ 'N') => {
-                        disable_raw_mode()?;
-                        println!("{}", c);
+                        f.disable_raw_mode()?;
+                        queue!(f, Print(c))?;
                         break false;
                     }
                     KeyCode::Enter if default.is_some() => {
-                        disable_raw_mode()?;
+                        f.disable_raw_mode()?;
                         let default = default.unwrap();
                         let repr = if default { 'Y' } else { 'N' };
-                        println!("{}", repr);
+                        queue!(f, Print(repr))?;
                         break default;
                     }
                     _ => {}
@@ -157,6 +776,7 @@ where
         };
 
         style.style(f, &EndInput)?;
+        writeln!(f)?;
 
         Ok(is_yes)
     }
@@ -175,6 +795,164 @@ where
     }
 }
 
+/// A query for text which spans multiple lines.
+///
+/// Enter inserts a newline, and Ctrl-D or Alt-Enter submits the text.
+pub struct MultiLineQuery<'a, S> {
+    prompt: Prompt,
+    style: &'a S,
+    reader: MultiLineReader,
+}
+
+impl<'a, S> MultiLineQuery<'a, S> {
+    /// Starts with `text` in the input, which the user can edit.
+    pub fn with_initial_text(self, text: impl Into<String>) -> Self {
+        Self {
+            reader: MultiLineReader::with_text(text),
+            ..self
+        }
+    }
+}
+
+impl<'a, S> Query for MultiLineQuery<'a, S>
+where
+    S: Styler<Prompt> + Styler<BeginInput> + Styler<EndInput>,
+{
+    type Result = String;
+
+    fn show_with(
+        self,
+        f: &mut impl Backend,
+        events: &mut impl EventSource,
+    ) -> Result<Self::Result> {
+        let Self {
+            prompt,
+            style,
+            mut reader,
+        } = self;
+
+        if !events.is_interactive() {
+            // The whole input is the answer, as it may span multiple lines.
+            let mut input = String::new();
+            if io::stdin().read_to_string(&mut input)? == 0 {
+                return Err(Error::NotATty);
+            }
+            let mut answer = input.replace("\r\n", "\n");
+            if answer.ends_with('\n') {
+                answer.pop();
+            }
+            return Ok(answer);
+        }
+        let f = &mut TerminalGuard::new(f, events.is_terminal());
+
+        style.style(f, &prompt)?;
+        style.style(f, &BeginInput)?;
+        f.flush()?;
+        let (x, _) = f.cursor_position()?;
+        let mut cursor_row = 0;
+        draw_block(f, x, &reader, &mut cursor_row, false)?;
+        f.enable_bracketed_paste()?;
+        f.flush()?;
+        f.enable_raw_mode()?;
+        let is_cancelled = loop {
+            let redraw = match events.read()? {
+                Event::Key(event) if event.kind != KeyEventKind::Release => {
+                    match (event.code, event.modifiers) {
+                        _ if is_cancel_key(&event, true) => break true,
+                        (KeyCode::Char('d'), KeyModifiers::CONTROL)
+                        | (KeyCode::Enter, KeyModifiers::ALT) => break false,
+                        _ => reader.on_key(&event),
+                    }
+                }
+                Event::Paste(text) => reader.on_paste(&text),
+                _ => false,
+            };
+            if redraw {
+                f.disable_raw_mode()?;
+                draw_block(f, x, &reader, &mut cursor_row, false)?;
+                f.flush()?;
+                f.enable_raw_mode()?;
+            }
+        };
+        f.disable_raw_mode()?;
+        f.disable_bracketed_paste()?;
+        draw_block(f, x, &reader, &mut cursor_row, true)?;
+        style.style(f, &EndInput)?;
+        writeln!(f)?;
+        if is_cancelled {
+            return Err(Error::Interrupted);
+        }
+
+        Ok(reader.get_result())
+    }
+}
+
+/// Draws the text of a multi-line input as a block whose rows start at column `x`.
+///
+/// `cursor_row` is the row of the block where the terminal cursor was left.
+/// The block is rewound to its first row with [`util::rewind`](crate::util::rewind),
+/// as a [`SelectHandler`](super::SelectHandler) does, and redrawn from there
+/// so that it can grow and shrink as the text changes.
+fn draw_block(
+    f: &mut impl Backend,
+    x: u16,
+    reader: &MultiLineReader,
+    cursor_row: &mut u16,
+    is_done: bool,
+) -> io::Result<()> {
+    queue!(f, Hide)?;
+    rewind(f, cursor_row)?;
+
+    let (width, _) = f.size()?;
+    // A column is left for the cursor after the last character of a row.
+    let width = width.saturating_sub(x + 1).max(1) as usize;
+    let text = reader.text();
+    let rows = wrap(text, width);
+    for (i, (_, row)) in rows.iter().enumerate() {
+        if i > 0 {
+            writeln!(f)?;
+        }
+        queue!(
+            f,
+            MoveToColumn(x),
+            Print(row),
+            Clear(ClearType::UntilNewLine)
+        )?;
+    }
+    queue!(f, Clear(ClearType::FromCursorDown))?;
+    if is_done {
+        return queue!(f, Show);
+    }
+
+    let cursor = reader.cursor();
+    let row = rows
+        .iter()
+        .rposition(|&(start, _)| start <= cursor)
+        .unwrap_or(0);
+    let (start, _) = rows[row];
+    let rows_below = (rows.len() - 1 - row) as u16;
+    if rows_below > 0 {
+        queue!(f, MoveToPreviousLine(rows_below))?;
+    }
+    let column = x + text[start..cursor].width_cjk() as u16;
+    *cursor_row = row as u16;
+    queue!(f, MoveToColumn(column), Show)
+}
+
+impl<'a, S> QueryBuilder<'a, S>
+where
+    S: Styler<Prompt> + Styler<BeginInput> + Styler<EndInput>,
+{
+    /// Asks for a text which spans multiple lines.
+    pub fn multiline(self) -> MultiLineQuery<'a, S> {
+        MultiLineQuery {
+            prompt: Prompt(self.prompt.unwrap_or_default()),
+            style: self.style,
+            reader: MultiLineReader::default(),
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct EditorQuery {
     editor: Option<OsString>,
@@ -203,10 +981,15 @@ impl EditorQuery {
 impl Query for EditorQuery {
     type Result = Option<String>;
 
-    fn show_on(self, _: &mut impl Write) -> Result<Self::Result> {
+    fn show_with(self, _: &mut impl Backend, _: &mut impl EventSource) -> Result<Self::Result> {
         let temp = tempfile::NamedTempFile::new()?;
 
-        if self.editor().arg(temp.path()).spawn()?.wait()?.success() {
+        let mut editor = self
+            .editor()
+            .arg(temp.path())
+            .spawn()
+            .map_err(Error::Editor)?;
+        if editor.wait()?.success() {
             let mut text = String::new();
             temp.into_file().read_to_string(&mut text)?;
             Ok(Some(text))
@@ -1,8 +1,15 @@
-use std::io::{stderr, Write};
+use std::{
+    fs::OpenOptions,
+    io::{self, stderr, stdin, BufRead, Write},
+};
 
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::event::{EventSource, TerminalEvents};
 use crate::item::Prompt;
 use crate::style::{DefaultStyle, Styler};
-use crate::Result;
+use crate::terminal::{Backend, TerminalBackend};
+use crate::{Error, Result};
 
 mod input;
 mod reader;
@@ -45,6 +52,15 @@ impl Default for QueryBuilder<'_, DefaultStyle> {
     }
 }
 
+/// A question shown to the user.
+///
+/// The user can cancel a query with Esc or Ctrl-C, in which case [`Error::Interrupted`]
+/// is returned.
+///
+/// If stdin or stderr is not a terminal, nothing is drawn and the answer is read
+/// from stdin as a line instead. A query which gets no answer and has no default
+/// returns [`Error::NotATty`]. A select query takes the label of an item,
+/// or its number counted from 1.
 pub trait Query: Sized {
     type Result;
 
@@ -52,5 +68,49 @@ pub trait Query: Sized {
         self.show_on(&mut stderr())
     }
 
-    fn show_on(self, f: &mut impl Write) -> Result<Self::Result>;
+    fn show_on(self, f: &mut impl Write) -> Result<Self::Result> {
+        self.show_with(&mut TerminalBackend::new(f), &mut TerminalEvents::default())
+    }
+
+    /// Shows the query on the controlling terminal, even if stdout and stderr are redirected.
+    ///
+    /// Returns [`Error::NotATty`] if there is no terminal to open.
+    fn show_on_tty(self) -> Result<Self::Result> {
+        let path = if cfg!(windows) { "CONOUT$" } else { "/dev/tty" };
+        let mut tty = OpenOptions::new()
+            .write(true)
+            .open(path)
+            .map_err(|_| Error::NotATty)?;
+        self.show_with(
+            &mut TerminalBackend::new(&mut tty),
+            &mut TerminalEvents::on_tty(),
+        )
+    }
+
+    /// Shows the query on `f`, reading the keys from `events` instead of the terminal.
+    fn show_with(self, f: &mut impl Backend, events: &mut impl EventSource)
+        -> Result<Self::Result>;
+}
+
+/// Returns `true` if the key cancels a query.
+///
+/// Esc is included only if `with_esc` is `true`, as some readers use it on their own.
+fn is_cancel_key(event: &KeyEvent, with_esc: bool) -> bool {
+    match (event.code, event.modifiers) {
+        (KeyCode::Char('c'), KeyModifiers::CONTROL) => true,
+        (KeyCode::Esc, _) => with_esc,
+        _ => false,
+    }
+}
+
+/// Reads an answer from stdin without the line break,
+/// or returns `None` if stdin has ended.
+fn read_answer() -> io::Result<Option<String>> {
+    let mut line = String::new();
+    if stdin().lock().read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    let len = line.trim_end_matches(['\r', '\n']).len();
+    line.truncate(len);
+    Ok(Some(line))
 }
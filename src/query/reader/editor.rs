@@ -0,0 +1,312 @@
+use std::ops::Range;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A single-line text buffer with a cursor and readline-like key bindings.
+///
+/// Killed text is kept in a kill ring owned by the editor,
+/// so that each reader has its own ring.
+///
+/// Every edit can be undone and redone. Consecutive typing or deletion of characters
+/// is merged into a single undo step, while a kill or a yank is always a step on its own.
+#[derive(Default)]
+pub(crate) struct LineEditor {
+    text: String,
+    cursor: usize,
+    kill_ring: Vec<String>,
+    /// The start of the last yanked text and its index in the kill ring.
+    last_yank: Option<(usize, usize)>,
+    undo: Vec<(String, usize)>,
+    redo: Vec<(String, usize)>,
+    /// The kind of the last edit, which decides whether the next edit is merged into it.
+    last_edit: Option<Edit>,
+    /// Whether edits are grouped, which outlasts undo and redo inside the group.
+    in_group: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Typing,
+    Deleting,
+    Other,
+    /// Every edit is merged until the group ends.
+    Group,
+}
+
+impl LineEditor {
+    /// Creates an editor with an initial text, placing the cursor at the end.
+    pub fn with_text(text: String) -> Self {
+        Self {
+            cursor: text.len(),
+            text,
+            ..Default::default()
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn into_text(self) -> String {
+        self.text
+    }
+
+    /// Replaces the whole text, which can be undone, and moves the cursor to the end.
+    pub fn set_text(&mut self, text: String) {
+        self.save(Edit::Other);
+        self.cursor = text.len();
+        self.text = text;
+        self.last_yank = None;
+    }
+
+    /// Replaces the text in `range` as a single undo step,
+    /// and moves the cursor to the end of the replacement.
+    pub fn replace_range(&mut self, range: Range<usize>, replace_with: &str) {
+        self.save(Edit::Other);
+        self.cursor = range.start + replace_with.len();
+        self.text.replace_range(range, replace_with);
+        self.last_yank = None;
+    }
+
+    /// Inserts `text` at the cursor as a single undo step.
+    pub fn paste(&mut self, text: &str) -> bool {
+        if text.is_empty() {
+            return false;
+        }
+        self.replace_range(self.cursor..self.cursor, text);
+        true
+    }
+
+    /// Saves the current state as a separate undo step.
+    pub fn checkpoint(&mut self) {
+        self.save(Edit::Other);
+    }
+
+    /// Saves the current state and merges every edit into one undo step
+    /// until [`LineEditor::end_group`] is called.
+    ///
+    /// Undoing or redoing inside the group keeps it, and the edits after it
+    /// are merged into a new step.
+    pub fn begin_group(&mut self) {
+        self.in_group = true;
+        self.save(Edit::Group);
+    }
+
+    pub fn end_group(&mut self) {
+        self.in_group = false;
+        if self.last_edit == Some(Edit::Group) {
+            self.last_edit = None;
+            self.drop_unchanged_group();
+        }
+    }
+
+    /// Removes the step saved for the current group if nothing has been edited in it.
+    fn drop_unchanged_group(&mut self) {
+        if matches!(self.undo.last(), Some((text, _)) if *text == self.text) {
+            self.undo.pop();
+        }
+    }
+
+    pub fn undo(&mut self) -> bool {
+        if self.last_edit == Some(Edit::Group) {
+            self.drop_unchanged_group();
+        }
+        match self.undo.pop() {
+            Some((text, cursor)) => {
+                let text = std::mem::replace(&mut self.text, text);
+                self.redo.push((text, self.cursor));
+                self.cursor = cursor;
+                self.last_edit = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn redo(&mut self) -> bool {
+        match self.redo.pop() {
+            Some((text, cursor)) => {
+                let text = std::mem::replace(&mut self.text, text);
+                self.undo.push((text, self.cursor));
+                self.cursor = cursor;
+                self.last_edit = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn save(&mut self, edit: Edit) {
+        let edit = if self.in_group { Edit::Group } else { edit };
+        match (self.last_edit, edit) {
+            (Some(Edit::Group), _) => return,
+            (Some(last), Edit::Typing) | (Some(last), Edit::Deleting) if last == edit => return,
+            _ => {}
+        }
+        self.undo.push((self.text.clone(), self.cursor));
+        self.redo.clear();
+        self.last_edit = Some(edit);
+    }
+
+    /// Handles a key event and returns `true` if the text or the cursor has changed.
+    pub fn on_key(&mut self, event: &KeyEvent) -> bool {
+        let last_yank = self.last_yank.take();
+
+        match (event.code, event.modifiers) {
+            (KeyCode::Char('a'), KeyModifiers::CONTROL) | (KeyCode::Home, _) => self.move_to(0),
+            (KeyCode::Char('e'), KeyModifiers::CONTROL) | (KeyCode::End, _) => {
+                self.move_to(self.text.len())
+            }
+            (KeyCode::Left, KeyModifiers::CONTROL) | (KeyCode::Char('b'), KeyModifiers::ALT) => {
+                self.move_to(prev_word_start(&self.text, self.cursor))
+            }
+            (KeyCode::Right, KeyModifiers::CONTROL) | (KeyCode::Char('f'), KeyModifiers::ALT) => {
+                self.move_to(next_word_end(&self.text, self.cursor))
+            }
+            (KeyCode::Left, _) => match prev_boundary(&self.text, self.cursor) {
+                Some(prev) => self.move_to(prev),
+                None => false,
+            },
+            (KeyCode::Right, _) => match next_boundary(&self.text, self.cursor) {
+                Some(next) => self.move_to(next),
+                None => false,
+            },
+            (KeyCode::Backspace, _) => match prev_boundary(&self.text, self.cursor) {
+                Some(prev) => {
+                    self.save(Edit::Deleting);
+                    self.delete(prev, self.cursor)
+                }
+                None => false,
+            },
+            (KeyCode::Delete, _) => match next_boundary(&self.text, self.cursor) {
+                Some(next) => {
+                    self.save(Edit::Deleting);
+                    self.delete(self.cursor, next)
+                }
+                None => false,
+            },
+            (KeyCode::Char('z'), modifiers) | (KeyCode::Char('Z'), modifiers)
+                if modifiers == KeyModifiers::CONTROL | KeyModifiers::SHIFT =>
+            {
+                self.redo()
+            }
+            (KeyCode::Char('Z'), KeyModifiers::CONTROL) => self.redo(),
+            // Ctrl-_ is reported as Ctrl-7 by some terminals.
+            (KeyCode::Char('z'), KeyModifiers::CONTROL)
+            | (KeyCode::Char('_'), KeyModifiers::CONTROL)
+            | (KeyCode::Char('7'), KeyModifiers::CONTROL) => self.undo(),
+            (KeyCode::Char('w'), KeyModifiers::CONTROL) => {
+                self.kill(prev_word_start(&self.text, self.cursor), self.cursor)
+            }
+            (KeyCode::Char('u'), KeyModifiers::CONTROL) => self.kill(0, self.cursor),
+            (KeyCode::Char('k'), KeyModifiers::CONTROL) => self.kill(self.cursor, self.text.len()),
+            (KeyCode::Char('y'), KeyModifiers::CONTROL) => match self.kill_ring.len() {
+                0 => false,
+                len => {
+                    self.save(Edit::Other);
+                    self.yank(len - 1)
+                }
+            },
+            (KeyCode::Char('y'), KeyModifiers::ALT) => match last_yank {
+                Some((start, index)) => {
+                    self.delete(start, self.cursor);
+                    let index = index.checked_sub(1).unwrap_or(self.kill_ring.len() - 1);
+                    self.yank(index)
+                }
+                None => false,
+            },
+            (KeyCode::Char(c), modifiers) if (modifiers - KeyModifiers::SHIFT).is_empty() => {
+                self.save(Edit::Typing);
+                self.insert(c)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn move_to(&mut self, cursor: usize) -> bool {
+        let moved = self.cursor != cursor;
+        self.cursor = cursor;
+        if moved && self.last_edit != Some(Edit::Group) {
+            self.last_edit = None;
+        }
+        moved
+    }
+
+    fn insert(&mut self, c: char) -> bool {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+        true
+    }
+
+    pub fn delete(&mut self, start: usize, end: usize) -> bool {
+        if start == end {
+            return false;
+        }
+        self.text.replace_range(start..end, "");
+        self.cursor = start;
+        true
+    }
+
+    pub fn kill(&mut self, start: usize, end: usize) -> bool {
+        if start == end {
+            return false;
+        }
+        self.save(Edit::Other);
+        self.kill_ring.push(self.text[start..end].to_string());
+        self.delete(start, end)
+    }
+
+    fn yank(&mut self, index: usize) -> bool {
+        let start = self.cursor;
+        self.text.insert_str(start, &self.kill_ring[index]);
+        self.cursor += self.kill_ring[index].len();
+        self.last_yank = Some((start, index));
+        true
+    }
+}
+
+/// Returns the start of the grapheme cluster before `at`.
+pub(crate) fn prev_boundary(text: &str, at: usize) -> Option<usize> {
+    text[..at]
+        .grapheme_indices(true)
+        .next_back()
+        .map(|(i, _)| i)
+}
+
+/// Returns the end of the grapheme cluster after `at`.
+pub(crate) fn next_boundary(text: &str, at: usize) -> Option<usize> {
+    text[at..].graphemes(true).next().map(|g| at + g.len())
+}
+
+/// Returns the start of the word before `at`.
+pub(crate) fn prev_word_start(text: &str, at: usize) -> usize {
+    text[..at]
+        .split_word_bound_indices()
+        .rev()
+        .find(|(_, word)| is_word(word))
+        .map_or(0, |(i, _)| i)
+}
+
+/// Returns the end of the word after `at`.
+pub(crate) fn next_word_end(text: &str, at: usize) -> usize {
+    text[at..]
+        .split_word_bound_indices()
+        .find(|(_, word)| is_word(word))
+        .map_or(text.len(), |(i, word)| at + i + word.len())
+}
+
+/// Returns the start of the word after the one at `at`.
+pub(crate) fn next_word_start(text: &str, at: usize) -> usize {
+    text.split_word_bound_indices()
+        .find(|&(i, word)| i > at && is_word(word))
+        .map_or(text.len(), |(i, _)| i)
+}
+
+fn is_word(segment: &str) -> bool {
+    segment.chars().any(char::is_alphanumeric)
+}
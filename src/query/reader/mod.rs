@@ -0,0 +1,241 @@
+use std::ops::Range;
+
+use crossterm::event::KeyEvent;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::item::EditMode;
+
+mod editor;
+mod multiline;
+mod vi;
+
+use editor::LineEditor;
+pub use multiline::*;
+pub use vi::*;
+
+pub trait TextReader {
+    /// Handles a key event and returns `true` if redraw is required.
+    fn on_key(&mut self, event: &KeyEvent) -> bool;
+    /// Returns the text to be shown.
+    fn text(&self) -> &str;
+    /// Returns the position of the cursor as a byte offset into [`TextReader::text`].
+    fn cursor(&self) -> usize;
+    /// Returns the current editing mode, if the reader is modal.
+    fn edit_mode(&self) -> Option<EditMode> {
+        None
+    }
+    /// Returns `true` if the reader uses Esc on its own,
+    /// in which case only Ctrl-C cancels the query.
+    fn handles_esc(&self) -> bool {
+        false
+    }
+    /// Replaces the text and moves the cursor to the end.
+    fn set_text(&mut self, text: String);
+    /// Replaces the text in `range`, which is in bytes of [`TextReader::text`],
+    /// and moves the cursor to the end of the replacement.
+    fn replace_range(&mut self, range: Range<usize>, replace_with: &str);
+    /// Inserts pasted text at the cursor as a single edit, and returns `true` if redraw is required.
+    ///
+    /// Line breaks are removed from the text, as the reader reads a single line.
+    fn on_paste(&mut self, text: &str) -> bool {
+        let text = strip_line_breaks(text);
+        if text.is_empty() {
+            return false;
+        }
+        let cursor = self.cursor();
+        self.replace_range(cursor..cursor, &text);
+        true
+    }
+    /// Returns the text which will be the result, which may differ from [`TextReader::text`].
+    fn input(&self) -> &str {
+        self.text()
+    }
+    fn get_result(self) -> String;
+}
+
+/// A reader whose text is not secret, so that it can be kept in a history,
+/// completed or suggested.
+pub trait ClearTextReader: TextReader {}
+
+impl ClearTextReader for PlainReader {}
+
+impl ClearTextReader for ViReader {}
+
+fn strip_line_breaks(text: &str) -> String {
+    text.chars().filter(|&c| c != '\r' && c != '\n').collect()
+}
+
+#[derive(Default)]
+pub struct PlainReader {
+    editor: LineEditor,
+}
+
+impl PlainReader {
+    /// Creates a reader whose text starts with `text`.
+    pub fn with_text(text: impl Into<String>) -> Self {
+        Self {
+            editor: LineEditor::with_text(text.into()),
+        }
+    }
+}
+
+impl TextReader for PlainReader {
+    fn on_key(&mut self, event: &KeyEvent) -> bool {
+        self.editor.on_key(event)
+    }
+
+    fn text(&self) -> &str {
+        self.editor.text()
+    }
+
+    fn cursor(&self) -> usize {
+        self.editor.cursor()
+    }
+
+    fn set_text(&mut self, text: String) {
+        self.editor.set_text(text);
+    }
+
+    fn replace_range(&mut self, range: Range<usize>, replace_with: &str) {
+        self.editor.replace_range(range, replace_with);
+    }
+
+    fn get_result(self) -> String {
+        self.editor.into_text()
+    }
+}
+
+pub struct SecretReader<S> {
+    shield: S,
+    password: LineEditor,
+}
+
+impl<S> SecretReader<S> {
+    pub fn new(shield: S) -> Self {
+        Self {
+            shield,
+            password: LineEditor::default(),
+        }
+    }
+}
+
+impl<S> TextReader for SecretReader<S>
+where
+    S: Shield,
+{
+    fn on_key(&mut self, event: &KeyEvent) -> bool {
+        let cursor = self.cursor();
+        if self.password.on_key(event) {
+            let masked = self
+                .shield
+                .mask(self.password.text().graphemes(true).count());
+            masked || self.cursor() != cursor
+        } else {
+            false
+        }
+    }
+
+    fn text(&self) -> &str {
+        self.shield.text()
+    }
+
+    fn cursor(&self) -> usize {
+        let password = self.password.text();
+        self.shield
+            .offset(password[..self.password.cursor()].graphemes(true).count())
+    }
+
+    fn set_text(&mut self, text: String) {
+        self.password.set_text(text);
+        self.shield
+            .mask(self.password.text().graphemes(true).count());
+    }
+
+    fn replace_range(&mut self, range: Range<usize>, replace_with: &str) {
+        // Finds the grapheme clusters of the password which are masked at the range.
+        let password = self.password.text();
+        let to_byte = |offset: usize| {
+            let index = (0..password.graphemes(true).count())
+                .take_while(|&i| self.shield.offset(i) < offset)
+                .count();
+            password
+                .grapheme_indices(true)
+                .nth(index)
+                .map_or(password.len(), |(i, _)| i)
+        };
+        let range = to_byte(range.start)..to_byte(range.end);
+        self.password.replace_range(range, replace_with);
+        self.shield
+            .mask(self.password.text().graphemes(true).count());
+    }
+
+    fn on_paste(&mut self, text: &str) -> bool {
+        // The cursor of the mask may not tell where the password is edited.
+        let pasted = self.password.paste(&strip_line_breaks(text));
+        self.shield
+            .mask(self.password.text().graphemes(true).count());
+        pasted
+    }
+
+    fn input(&self) -> &str {
+        self.password.text()
+    }
+
+    fn get_result(self) -> String {
+        self.password.into_text()
+    }
+}
+
+/// Hides a password from the screen.
+pub trait Shield {
+    /// Masks a password of `len` grapheme clusters and returns `true` if the mask has changed.
+    fn mask(&mut self, len: usize) -> bool;
+    fn text(&self) -> &str;
+    /// Returns the byte offset into [`Shield::text`] of the `index`-th masked grapheme cluster.
+    fn offset(&self, index: usize) -> usize;
+}
+
+pub struct EmptyShield;
+impl Shield for EmptyShield {
+    fn mask(&mut self, _: usize) -> bool {
+        false
+    }
+
+    fn text(&self) -> &str {
+        ""
+    }
+
+    fn offset(&self, _: usize) -> usize {
+        0
+    }
+}
+
+pub struct CharacterShield {
+    c: char,
+    buffer: String,
+}
+
+impl CharacterShield {
+    pub fn new(c: char) -> Self {
+        Self {
+            c,
+            buffer: String::new(),
+        }
+    }
+}
+
+impl Shield for CharacterShield {
+    fn mask(&mut self, len: usize) -> bool {
+        let old_len = self.buffer.len();
+        self.buffer = self.c.to_string().repeat(len);
+        self.buffer.len() != old_len
+    }
+
+    fn text(&self) -> &str {
+        &self.buffer
+    }
+
+    fn offset(&self, index: usize) -> usize {
+        index * self.c.len_utf8()
+    }
+}
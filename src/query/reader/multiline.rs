@@ -0,0 +1,106 @@
+use std::ops::Range;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use super::{LineEditor, TextReader};
+
+/// A reader for text which spans multiple lines.
+///
+/// Enter inserts a newline, and Up and Down move the cursor between lines.
+/// Line-wise key bindings, such as Home, End, Ctrl-U and Ctrl-K, act on the current line,
+/// and everything else behaves like [`PlainReader`](super::PlainReader).
+#[derive(Default)]
+pub struct MultiLineReader {
+    editor: LineEditor,
+}
+
+impl MultiLineReader {
+    /// Creates a reader whose text starts with `text`.
+    pub fn with_text(text: impl Into<String>) -> Self {
+        Self {
+            editor: LineEditor::with_text(text.into()),
+        }
+    }
+
+    /// Returns the range of the line containing `at`, excluding the newline.
+    fn line_at(&self, at: usize) -> Range<usize> {
+        let text = self.editor.text();
+        let start = text[..at].rfind('\n').map_or(0, |i| i + 1);
+        let end = text[at..].find('\n').map_or(text.len(), |i| at + i);
+        start..end
+    }
+
+    /// Moves the cursor to the line which contains `at`, keeping the column if possible.
+    fn move_to_line(&mut self, at: usize) -> bool {
+        let text = self.editor.text();
+        let current = self.line_at(self.editor.cursor());
+        let column = text[current.start..self.editor.cursor()].width_cjk();
+        let line = self.line_at(at);
+        let mut target = line.start;
+        let mut line_width = 0;
+        for (i, grapheme) in text[line.clone()].grapheme_indices(true) {
+            line_width += grapheme.width_cjk();
+            if line_width > column {
+                break;
+            }
+            target = line.start + i + grapheme.len();
+        }
+        self.editor.move_to(target)
+    }
+}
+
+impl TextReader for MultiLineReader {
+    fn on_key(&mut self, event: &KeyEvent) -> bool {
+        let cursor = self.editor.cursor();
+        let line = self.line_at(cursor);
+        match (event.code, event.modifiers) {
+            (KeyCode::Enter, KeyModifiers::NONE) => {
+                self.editor.replace_range(cursor..cursor, "\n");
+                true
+            }
+            (KeyCode::Up, _) => match line.start.checked_sub(1) {
+                Some(prev) => self.move_to_line(prev),
+                None => false,
+            },
+            (KeyCode::Down, _) if line.end < self.editor.text().len() => {
+                self.move_to_line(line.end + 1)
+            }
+            (KeyCode::Char('a'), KeyModifiers::CONTROL) | (KeyCode::Home, KeyModifiers::NONE) => {
+                self.editor.move_to(line.start)
+            }
+            (KeyCode::Char('e'), KeyModifiers::CONTROL) | (KeyCode::End, KeyModifiers::NONE) => {
+                self.editor.move_to(line.end)
+            }
+            (KeyCode::Char('u'), KeyModifiers::CONTROL) => self.editor.kill(line.start, cursor),
+            (KeyCode::Char('k'), KeyModifiers::CONTROL) => self.editor.kill(cursor, line.end),
+            _ => self.editor.on_key(event),
+        }
+    }
+
+    fn text(&self) -> &str {
+        self.editor.text()
+    }
+
+    fn cursor(&self) -> usize {
+        self.editor.cursor()
+    }
+
+    fn set_text(&mut self, text: String) {
+        self.editor.set_text(text);
+    }
+
+    fn replace_range(&mut self, range: Range<usize>, replace_with: &str) {
+        self.editor.replace_range(range, replace_with);
+    }
+
+    fn on_paste(&mut self, text: &str) -> bool {
+        self.editor
+            .paste(&text.replace("\r\n", "\n").replace('\r', "\n"))
+    }
+
+    fn get_result(self) -> String {
+        self.editor.into_text()
+    }
+}
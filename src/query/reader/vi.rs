@@ -0,0 +1,267 @@
+use std::ops::Range;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::item::EditMode;
+
+use super::{
+    editor::{next_boundary, next_word_end, next_word_start, prev_boundary, prev_word_start},
+    LineEditor, TextReader,
+};
+
+/// A reader with vi-like modal editing.
+///
+/// It starts in insert mode, which behaves like [`PlainReader`](super::PlainReader).
+/// Pressing Esc switches to normal mode, where motions and operators can be used.
+pub struct ViReader {
+    editor: LineEditor,
+    mode: EditMode,
+    count: Option<usize>,
+    /// A pending operator with its count.
+    operator: Option<(char, usize)>,
+}
+
+impl Default for ViReader {
+    fn default() -> Self {
+        Self::with_text(String::new())
+    }
+}
+
+impl ViReader {
+    /// Creates a reader whose text starts with `text`, in insert mode.
+    pub fn with_text(text: impl Into<String>) -> Self {
+        let mut editor = LineEditor::with_text(text.into());
+        // Everything typed in the initial insert mode is a single undo step.
+        editor.begin_group();
+        Self {
+            editor,
+            mode: EditMode::Insert,
+            count: None,
+            operator: None,
+        }
+    }
+
+    fn on_insert_key(&mut self, event: &KeyEvent) -> bool {
+        if event.code == KeyCode::Esc {
+            self.mode = EditMode::Normal;
+            self.editor.end_group();
+            let text = self.editor.text();
+            if let Some(prev) = prev_boundary(text, self.editor.cursor()) {
+                self.editor.move_to(prev);
+            }
+            true
+        } else {
+            self.editor.on_key(event)
+        }
+    }
+
+    fn on_normal_key(&mut self, event: &KeyEvent) -> bool {
+        let c = match event.code {
+            KeyCode::Char('r') if event.modifiers == KeyModifiers::CONTROL => {
+                let redone = self.editor.redo();
+                self.clamp_cursor();
+                return redone;
+            }
+            KeyCode::Char(c) if (event.modifiers - KeyModifiers::SHIFT).is_empty() => c,
+            KeyCode::Left => 'h',
+            KeyCode::Right => 'l',
+            KeyCode::Home => '0',
+            KeyCode::End => '$',
+            KeyCode::Esc => {
+                // Esc is used only if it has something to discard.
+                let is_pending = self.count.is_some() || self.operator.is_some();
+                self.count = None;
+                self.operator = None;
+                return is_pending;
+            }
+            _ => return false,
+        };
+
+        match c {
+            '1'..='9' | '0' if c != '0' || self.count.is_some() => {
+                let digit = c.to_digit(10).unwrap() as usize;
+                // No motion can go further than the text is long, so the count is capped there.
+                let max = self.editor.text().graphemes(true).count().max(1);
+                let count = self.count.unwrap_or(0).saturating_mul(10);
+                self.count = Some(count.saturating_add(digit).min(max));
+                return false;
+            }
+            _ => {}
+        }
+        let count = self.count.take().unwrap_or(1);
+
+        if let Some((operator, operator_count)) = self.operator.take() {
+            let cursor = self.editor.cursor();
+            // `cw` changes until the end of the word, just like `ce`.
+            let c = if operator == 'c' && c == 'w' { 'e' } else { c };
+            let range = if c == operator {
+                Some((0, self.editor.text().len()))
+            } else {
+                self.motion(c, operator_count.saturating_mul(count), true)
+                    .map(|target| (cursor.min(target), cursor.max(target)))
+            };
+            return match range {
+                Some((start, end)) => self.apply(operator, start, end),
+                None => false,
+            };
+        }
+
+        let cursor = self.editor.cursor();
+        let len = self.editor.text().len();
+        match c {
+            'i' => self.insert_at(cursor),
+            'a' => self.insert_at(next_boundary(self.editor.text(), cursor).unwrap_or(len)),
+            'I' => self.insert_at(0),
+            'A' => self.insert_at(len),
+            'x' => {
+                let end = step_repeatedly(count, cursor, |at| {
+                    next_boundary(self.editor.text(), at).unwrap_or(at)
+                });
+                self.apply('d', cursor, end)
+            }
+            'D' => self.apply('d', cursor, len),
+            'C' => self.apply('c', cursor, len),
+            'd' | 'c' => {
+                self.operator = Some((c, count));
+                false
+            }
+            'u' => {
+                let undone = self.editor.undo();
+                self.clamp_cursor();
+                undone
+            }
+            _ => match self.motion(c, count, false) {
+                Some(target) => {
+                    let moved = self.editor.move_to(target);
+                    self.clamp_cursor() || moved
+                }
+                None => false,
+            },
+        }
+    }
+
+    /// Returns the destination of a motion.
+    ///
+    /// If `is_operator` is `true`, the destination is the exclusive end of the range
+    /// an operator applies to.
+    fn motion(&self, c: char, count: usize, is_operator: bool) -> Option<usize> {
+        let text = self.editor.text();
+        let cursor = self.editor.cursor();
+        let repeat = |step: &dyn Fn(usize) -> usize| step_repeatedly(count, cursor, step);
+        let target = match c {
+            'h' => repeat(&|at| prev_boundary(text, at).unwrap_or(at)),
+            'l' => repeat(&|at| next_boundary(text, at).unwrap_or(at)),
+            'w' => repeat(&|at| next_word_start(text, at)),
+            'b' => repeat(&|at| prev_word_start(text, at)),
+            'e' => {
+                let end = repeat(&|at| {
+                    let at = next_boundary(text, at).unwrap_or(at);
+                    next_word_end(text, at)
+                });
+                if is_operator {
+                    return Some(end);
+                }
+                prev_boundary(text, end).unwrap_or(end)
+            }
+            '0' => 0,
+            '$' => text.len(),
+            _ => return None,
+        };
+        Some(target)
+    }
+
+    /// Applies an operator to a range.
+    fn apply(&mut self, operator: char, start: usize, end: usize) -> bool {
+        if start == end && operator == 'd' {
+            return false;
+        }
+        if operator == 'c' {
+            self.editor.begin_group();
+            self.editor.delete(start, end);
+            self.mode = EditMode::Insert;
+        } else {
+            self.editor.checkpoint();
+            self.editor.delete(start, end);
+            self.clamp_cursor();
+        }
+        true
+    }
+
+    fn insert_at(&mut self, cursor: usize) -> bool {
+        self.editor.begin_group();
+        self.editor.move_to(cursor);
+        self.mode = EditMode::Insert;
+        true
+    }
+
+    /// Keeps the cursor on a character, as normal mode does not allow the cursor
+    /// to be placed after the last character.
+    fn clamp_cursor(&mut self) -> bool {
+        let text = self.editor.text();
+        if self.editor.cursor() == text.len() {
+            if let Some(last) = prev_boundary(text, text.len()) {
+                return self.editor.move_to(last);
+            }
+        }
+        false
+    }
+}
+
+impl TextReader for ViReader {
+    fn on_key(&mut self, event: &KeyEvent) -> bool {
+        match self.mode {
+            EditMode::Insert => self.on_insert_key(event),
+            EditMode::Normal => self.on_normal_key(event),
+        }
+    }
+
+    fn text(&self) -> &str {
+        self.editor.text()
+    }
+
+    fn cursor(&self) -> usize {
+        self.editor.cursor()
+    }
+
+    fn set_text(&mut self, text: String) {
+        self.editor.set_text(text);
+        if self.mode == EditMode::Normal {
+            self.clamp_cursor();
+        }
+    }
+
+    fn replace_range(&mut self, range: Range<usize>, replace_with: &str) {
+        self.editor.replace_range(range, replace_with);
+        if self.mode == EditMode::Normal {
+            self.clamp_cursor();
+        }
+    }
+
+    fn edit_mode(&self) -> Option<EditMode> {
+        Some(self.mode)
+    }
+
+    /// Esc switches to normal mode, and vi users press it out of habit,
+    /// so it never cancels the query.
+    fn handles_esc(&self) -> bool {
+        true
+    }
+
+    fn get_result(self) -> String {
+        self.editor.into_text()
+    }
+}
+
+/// Takes `step` from `start` up to `count` times, stopping once the position stops changing.
+fn step_repeatedly(count: usize, start: usize, step: impl Fn(usize) -> usize) -> usize {
+    let mut at = start;
+    for _ in 0..count {
+        let next = step(at);
+        if next == at {
+            break;
+        }
+        at = next;
+    }
+    at
+}
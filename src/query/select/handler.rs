@@ -1,68 +1,232 @@
-use std::{fmt::Display, io::Write};
+use std::{fmt::Display, io};
 
 use crossterm::{
-    cursor::MoveToPreviousLine,
-    event::{KeyCode, KeyEvent},
+    event::{KeyCode, KeyEvent, KeyModifiers},
     queue,
     terminal::{Clear, ClearType},
 };
 
-use crate::{item::ListItem, style::Styler, Result};
+use crate::{
+    item::ListItem,
+    matcher::{rank, Matcher},
+    style::Styler,
+    terminal::Backend,
+    util,
+};
 
 pub trait SelectHandler {
     type Result;
 
-    fn show(&mut self, f: &mut impl Write) -> Result<()>;
-    fn rewind(&mut self, f: &mut impl Write) -> Result<()>;
+    fn show(&mut self, f: &mut impl Backend) -> io::Result<()>;
+    fn rewind(&mut self, f: &mut impl Backend) -> io::Result<()>;
     /// Handles a key event and returns `true` if redraw is required.
     ///
     /// It should only handle movement events, such as Up and Down key.
+    /// A number key from 1 to 9 moves to the item shown at that row, and returns `true`
+    /// even if the cursor is already there, so that the item can be selected right away.
+    /// A letter moves to the next item starting with it, except j and k, which always
+    /// move down and up, so items starting with them cannot be reached that way.
     fn on_key(&mut self, key_event: &KeyEvent) -> bool;
     /// Toggles selection state of current cursor item.
     fn toggle(&mut self);
+    /// Shows only the items which `matcher` matches with `pattern`, ranked by score,
+    /// and moves the cursor to the first of them.
+    ///
+    /// Items which are filtered out keep their selection state.
+    fn filter(&mut self, matcher: &dyn Matcher, pattern: &str);
+    /// Returns the items in the list.
+    fn items(&self) -> &[ListItem];
+    /// Selects the item at `index`, which is used to answer without a terminal.
+    fn select_at(&mut self, index: usize);
     fn get_result(self) -> Self::Result;
 }
 
-pub struct ListHandler<'a, S> {
+pub struct ListHandler<'a, S, T> {
     style: &'a S,
+    entries: Entries<T>,
+    last_printed_rows: u16,
+}
+
+impl<'a, S, T> ListHandler<'a, S, T> {
+    pub fn new(style: &'a S, values: impl IntoIterator<Item = T>) -> Self
+    where
+        T: Display,
+    {
+        Self {
+            style,
+            entries: Entries::new(values),
+            last_printed_rows: 0,
+        }
+    }
+}
+
+/// Returns the key code of `key_event`, where j, k, Ctrl-N and Ctrl-P are read as arrow keys.
+fn navigation_key(key_event: &KeyEvent) -> KeyCode {
+    match (key_event.code, key_event.modifiers) {
+        (KeyCode::Char('j'), KeyModifiers::NONE) | (KeyCode::Char('n'), KeyModifiers::CONTROL) => {
+            KeyCode::Down
+        }
+        (KeyCode::Char('k'), KeyModifiers::NONE) | (KeyCode::Char('p'), KeyModifiers::CONTROL) => {
+            KeyCode::Up
+        }
+        (code, _) => code,
+    }
+}
+
+/// The items of a list with their values, and the items which are shown.
+struct Entries<T> {
     list: Vec<ListItem>,
+    values: Vec<T>,
+    /// The indices into `list` of the shown items, in the order they are shown.
+    view: Vec<usize>,
+    /// The index into `view` of the cursor item.
     cursor: usize,
-    last_printed_rows: u16,
 }
 
-impl<'a, S> ListHandler<'a, S> {
-    pub fn new(style: &'a S, list: &[impl Display]) -> Self {
-        let mut list: Vec<_> = list
+impl<T> Entries<T> {
+    /// Creates the entries for `values`, with the cursor on the first one.
+    fn new(values: impl IntoIterator<Item = T>) -> Self
+    where
+        T: Display,
+    {
+        let values: Vec<_> = values.into_iter().collect();
+        let list = values
             .iter()
-            .map(|item| ListItem {
-                item: item.to_string(),
+            .map(|value| ListItem {
+                item: value.to_string(),
                 is_cursor: false,
                 is_selected: false,
+                matches: vec![],
             })
             .collect();
-        if let Some(first) = list.get_mut(0) {
-            first.is_cursor = true;
-        }
-        Self {
-            style,
+        let mut entries = Self {
             list,
+            view: (0..values.len()).collect(),
+            values,
             cursor: 0,
-            last_printed_rows: 0,
+        };
+        entries.move_to(0);
+        entries
+    }
+
+    /// Returns the number of shown items.
+    fn len(&self) -> usize {
+        self.view.len()
+    }
+
+    /// Moves the cursor to the `index`-th shown item.
+    fn move_to(&mut self, index: usize) {
+        if let Some(&current) = self.view.get(self.cursor) {
+            self.list[current].is_cursor = false;
         }
+        self.cursor = index;
+        if let Some(&next) = self.view.get(self.cursor) {
+            self.list[next].is_cursor = true;
+        }
+    }
+
+    /// Returns the shown items from the `start`-th one, wrapping around to the first one.
+    fn shown_from(&self, start: usize) -> impl Iterator<Item = &ListItem> {
+        self.view
+            .iter()
+            .cycle()
+            .skip(start)
+            .take(self.view.len())
+            .map(move |&index| &self.list[index])
+    }
+
+    /// Moves the cursor by the keys which every handler shares, and returns `true` if it has moved.
+    ///
+    /// `page` items are shown from the `first_shown`-th one.
+    fn jump(&mut self, key_event: &KeyEvent, first_shown: usize, page: usize) -> bool {
+        let len = self.len();
+        if len == 0 {
+            return false;
+        }
+        let is_typed = (key_event.modifiers - KeyModifiers::SHIFT).is_empty();
+        let target = match key_event.code {
+            KeyCode::PageUp => self.cursor.saturating_sub(page),
+            KeyCode::PageDown => (self.cursor + page).min(len - 1),
+            KeyCode::Home => 0,
+            KeyCode::End => len - 1,
+            KeyCode::Char(c @ '1'..='9') if is_typed => {
+                let row = c as usize - '1' as usize;
+                if row >= page.min(len) {
+                    return false;
+                }
+                self.move_to((first_shown + row) % len);
+                return true;
+            }
+            // Jumps to the next item which starts with the letter.
+            KeyCode::Char(c) if is_typed && c.is_alphanumeric() => {
+                let c = c.to_lowercase().next();
+                let found = (1..=len).map(|i| (self.cursor + i) % len).find(|&i| {
+                    let item = &self.list[self.view[i]].item;
+                    item.chars()
+                        .next()
+                        .and_then(|first| first.to_lowercase().next())
+                        == c
+                });
+                match found {
+                    Some(target) => target,
+                    None => return false,
+                }
+            }
+            _ => return false,
+        };
+        if target == self.cursor {
+            return false;
+        }
+        self.move_to(target);
+        true
+    }
+
+    fn toggle(&mut self) {
+        if let Some(&current) = self.view.get(self.cursor) {
+            let is_selected = &mut self.list[current].is_selected;
+            *is_selected = !*is_selected;
+        }
+    }
+
+    fn filter(&mut self, matcher: &dyn Matcher, pattern: &str) {
+        let ranked = rank(
+            matcher,
+            pattern,
+            self.list.iter().map(|item| item.item.as_str()),
+        );
+        for item in &mut self.list {
+            item.is_cursor = false;
+            item.matches.clear();
+        }
+        self.view.clear();
+        for (index, found) in ranked {
+            self.list[index].matches = found.indices;
+            self.view.push(index);
+        }
+        self.move_to(0);
+    }
+
+    /// Returns the selected values in the order of the list.
+    fn into_selected(self) -> Vec<T> {
+        self.list
+            .into_iter()
+            .zip(self.values)
+            .filter_map(|(item, value)| item.is_selected.then_some(value))
+            .collect()
     }
 }
 
-impl<'a, S> SelectHandler for ListHandler<'a, S>
+impl<'a, S, T> SelectHandler for ListHandler<'a, S, T>
 where
     S: Styler<ListItem>,
 {
-    /// Returns a list of the index of the selected item and the value.
-    type Result = Vec<(usize, String)>;
+    /// Returns the selected values in the order of the list.
+    type Result = Vec<T>;
 
-    fn show(&mut self, f: &mut impl Write) -> Result<()> {
+    fn show(&mut self, f: &mut impl Backend) -> io::Result<()> {
         let mut printed_rows = 0;
 
-        for item in self.list.iter() {
+        for item in self.entries.shown_from(0) {
             self.style.style(f, item)?;
             queue!(f, Clear(ClearType::UntilNewLine))?;
             writeln!(f)?;
@@ -73,113 +237,105 @@ where
         Ok(())
     }
 
-    fn rewind(&mut self, f: &mut impl Write) -> Result<()> {
-        if self.last_printed_rows > 0 {
-            queue!(f, MoveToPreviousLine(self.last_printed_rows),)?;
-            self.last_printed_rows = 0;
-        }
-        Ok(())
+    fn rewind(&mut self, f: &mut impl Backend) -> io::Result<()> {
+        util::rewind(f, &mut self.last_printed_rows)
     }
 
     fn on_key(&mut self, key_event: &KeyEvent) -> bool {
-        match key_event.code {
-            KeyCode::Up if self.cursor > 0 => {
-                self.list[self.cursor].is_cursor = false;
-                self.cursor -= 1;
-                self.list[self.cursor].is_cursor = true;
+        let cursor = self.entries.cursor;
+        match navigation_key(key_event) {
+            KeyCode::Up if cursor > 0 => {
+                self.entries.move_to(cursor - 1);
                 true
             }
-            KeyCode::Down if self.cursor + 1 < self.list.len() => {
-                self.list[self.cursor].is_cursor = false;
-                self.cursor += 1;
-                self.list[self.cursor].is_cursor = true;
+            KeyCode::Down if cursor + 1 < self.entries.len() => {
+                self.entries.move_to(cursor + 1);
                 true
             }
-            _ => false,
+            KeyCode::Up | KeyCode::Down => false,
+            // Every item is shown, so a page is the whole list.
+            _ => self.entries.jump(key_event, 0, self.entries.len()),
         }
     }
 
     fn toggle(&mut self) {
-        if !self.list.is_empty() {
-            let is_selected = &mut self.list[self.cursor].is_selected;
-            *is_selected = !*is_selected;
-        }
+        self.entries.toggle();
+    }
+
+    fn filter(&mut self, matcher: &dyn Matcher, pattern: &str) {
+        self.entries.filter(matcher, pattern);
+    }
+
+    fn items(&self) -> &[ListItem] {
+        &self.entries.list
+    }
+
+    fn select_at(&mut self, index: usize) {
+        self.entries.list[index].is_selected = true;
     }
 
     fn get_result(self) -> Self::Result {
-        self.list
-            .into_iter()
-            .enumerate()
-            .filter_map(|(i, item)| item.is_selected.then(|| (i, item.item)))
-            .collect()
+        self.entries.into_selected()
     }
 }
 
-pub struct FixedRowHandler<'a, S> {
+pub struct FixedRowHandler<'a, S, T> {
     style: &'a S,
-    list: Vec<ListItem>,
-    cursor: usize,
+    entries: Entries<T>,
     rows: usize,
     last_printed_rows: u16,
 }
 
-impl<'a, S> FixedRowHandler<'a, S> {
-    pub fn new(style: &'a S, list: &[impl Display], rows: usize) -> Self {
-        let mut list: Vec<_> = list
-            .iter()
-            .map(|item| ListItem {
-                item: item.to_string(),
-                is_cursor: false,
-                is_selected: false,
-            })
-            .collect();
-        if let Some(first) = list.get_mut(0) {
-            first.is_cursor = true;
-        }
+impl<'a, S, T> FixedRowHandler<'a, S, T> {
+    pub fn new(style: &'a S, values: impl IntoIterator<Item = T>, rows: usize) -> Self
+    where
+        T: Display,
+    {
         Self {
             style,
-            list,
-            cursor: 0,
+            entries: Entries::new(values),
             rows,
             last_printed_rows: 0,
         }
     }
 
-    pub fn from_list_handler(list_handler: ListHandler<'a, S>, rows: usize) -> Self {
+    pub fn from_list_handler(list_handler: ListHandler<'a, S, T>, rows: usize) -> Self {
         Self {
             style: list_handler.style,
-            list: list_handler.list,
-            cursor: list_handler.cursor,
+            entries: list_handler.entries,
             rows,
             last_printed_rows: list_handler.last_printed_rows,
         }
     }
+
+    /// Returns the index of the item shown at the first row, which keeps the cursor near the middle.
+    fn first_shown(&self) -> usize {
+        let len = self.entries.len();
+        let cursor = self.entries.cursor;
+        if len < self.rows {
+            0
+        } else if self.rows >= 5 {
+            (cursor + len - 2) % len
+        } else if self.rows >= 3 {
+            (cursor + len - 1) % len
+        } else {
+            cursor
+        }
+    }
 }
 
-impl<'a, S> SelectHandler for FixedRowHandler<'a, S>
+impl<'a, S, T> SelectHandler for FixedRowHandler<'a, S, T>
 where
     S: Styler<ListItem>,
 {
-    /// Returns a list of the index of the selected item and the value.
-    type Result = Vec<(usize, String)>;
+    /// Returns the selected values in the order of the list.
+    type Result = Vec<T>;
 
-    fn show(&mut self, f: &mut impl Write) -> Result<()> {
+    fn show(&mut self, f: &mut impl Backend) -> io::Result<()> {
         let mut printed_rows = 0;
 
-        let start = if self.list.len() < self.rows {
-            0
-        } else if self.rows >= 5 {
-            (self.cursor + self.list.len() - 2) % self.list.len()
-        } else if self.rows >= 3 {
-            (self.cursor + self.list.len() - 1) % self.list.len()
-        } else {
-            self.cursor
-        };
-
-        let count = std::cmp::min(self.list.len(), self.rows);
-
-        let iter = self.list.iter().cycle().skip(start).take(count);
-        for item in iter {
+        let start = self.first_shown();
+        for item in self.entries.shown_from(start).take(self.rows) {
             self.style.style(f, item)?;
             queue!(f, Clear(ClearType::UntilNewLine))?;
             writeln!(f)?;
@@ -190,69 +346,63 @@ where
         Ok(())
     }
 
-    fn rewind(&mut self, f: &mut impl Write) -> Result<()> {
-        if self.last_printed_rows > 0 {
-            queue!(f, MoveToPreviousLine(self.last_printed_rows),)?;
-            self.last_printed_rows = 0;
-        }
-        Ok(())
+    fn rewind(&mut self, f: &mut impl Backend) -> io::Result<()> {
+        util::rewind(f, &mut self.last_printed_rows)
     }
 
     fn on_key(&mut self, key_event: &KeyEvent) -> bool {
-        match key_event.code {
+        let len = self.entries.len();
+        let cursor = self.entries.cursor;
+        match navigation_key(key_event) {
             KeyCode::Up => {
-                if self.rows > self.list.len() {
-                    if self.cursor > 0 {
-                        self.list[self.cursor].is_cursor = false;
-                        self.cursor -= 1;
-                        self.list[self.cursor].is_cursor = true;
+                if self.rows > len {
+                    if cursor > 0 {
+                        self.entries.move_to(cursor - 1);
                         true
                     } else {
                         false
                     }
                 } else {
-                    self.list[self.cursor].is_cursor = false;
-                    self.cursor += self.list.len();
-                    self.cursor -= 1;
-                    self.cursor %= self.list.len();
-                    self.list[self.cursor].is_cursor = true;
+                    self.entries.move_to((cursor + len - 1) % len);
                     true
                 }
             }
             KeyCode::Down => {
-                if self.rows > self.list.len() {
-                    if self.cursor + 1 < self.list.len() {
-                        self.list[self.cursor].is_cursor = false;
-                        self.cursor += 1;
-                        self.list[self.cursor].is_cursor = true;
+                if self.rows > len {
+                    if cursor + 1 < len {
+                        self.entries.move_to(cursor + 1);
                         true
                     } else {
                         false
                     }
                 } else {
-                    self.list[self.cursor].is_cursor = false;
-                    self.cursor += 1;
-                    self.cursor %= self.list.len();
-                    self.list[self.cursor].is_cursor = true;
+                    self.entries.move_to((cursor + 1) % len);
                     true
                 }
             }
-            _ => false,
+            _ => self
+                .entries
+                .jump(key_event, self.first_shown(), self.rows.min(len)),
         }
     }
 
     fn toggle(&mut self) {
-        if !self.list.is_empty() {
-            let is_selected = &mut self.list[self.cursor].is_selected;
-            *is_selected = !*is_selected;
-        }
+        self.entries.toggle();
+    }
+
+    fn filter(&mut self, matcher: &dyn Matcher, pattern: &str) {
+        self.entries.filter(matcher, pattern);
+    }
+
+    fn items(&self) -> &[ListItem] {
+        &self.entries.list
+    }
+
+    fn select_at(&mut self, index: usize) {
+        self.entries.list[index].is_selected = true;
     }
 
     fn get_result(self) -> Self::Result {
-        self.list
-            .into_iter()
-            .enumerate()
-            .filter_map(|(i, item)| item.is_selected.then(|| (i, item.item)))
-            .collect()
+        self.entries.into_selected()
     }
 }
@@ -10,15 +10,19 @@ use crate::{item::*, style::Styler};
 
 use super::QueryBuilder;
 
-type ListHandlerGen<'a, S, T> = Box<dyn FnMut(&[T]) -> ListHandler<'a, S> + 'a>;
+type ListHandlerGen<'a, S, T> = Box<dyn FnMut(Vec<T>) -> ListHandler<'a, S, T> + 'a>;
 
 impl<'a, S> QueryBuilder<'a, S>
 where
     S: Styler<Prompt> + Styler<BeginInput> + Styler<EndInput>,
 {
-    pub fn select<T>(self, list: &'a [T]) -> SelectQuery<'a, S, ListHandler<'a, S>>
+    /// Asks to select from `list`, returning the selected values themselves.
+    ///
+    /// A slice gives references to its items, while a [`Vec`] gives the items it owns.
+    pub fn select<I>(self, list: I) -> SelectQuery<'a, S, ListHandler<'a, S, I::Item>>
     where
-        T: Display,
+        I: IntoIterator,
+        I::Item: Display,
         S: Styler<ListItem>,
     {
         SelectQuery::new(
@@ -1,5 +1,8 @@
 use std::{
-    io::Write,
+    any::Any,
+    io::{self, Write},
+    marker::PhantomData,
+    panic::{self, AssertUnwindSafe},
     sync::{mpsc::sync_channel, Arc},
     thread,
     time::{Duration, Instant},
@@ -7,28 +10,39 @@ use std::{
 };
 
 use crossterm::{
-    cursor::{self, Hide, MoveToPreviousLine},
-    event::{self, Event, KeyCode},
+    cursor::{self, Hide, MoveToColumn, MoveToPreviousLine},
+    event::{Event, KeyCode, KeyEventKind},
     queue,
     style::Print,
-    terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType},
+    terminal::{Clear, ClearType},
 };
 use cursor::Show;
 
 use crate::{
-    item::{BeginInput, EndInput, ListItem, Overflow, Prompt, WaitMessage},
-    query::{PlainReader, Query, TextReader},
+    event::EventSource,
+    item::{BeginInput, EditMode, EndInput, ListItem, Overflow, Placeholder, Prompt, WaitMessage},
+    matcher::{FuzzyMatcher, Matcher},
+    query::{is_cancel_key, read_answer, PlainReader, Query, TextReader, ViReader},
     style::Styler,
-    util, Result,
+    terminal::{Backend, TerminalGuard},
+    util, Error, Result,
 };
 
 use super::{FixedRowHandler, ListHandler, SelectHandler};
 
-pub struct SelectQuery<'a, S, H> {
+/// Selects a single item, which [`SelectQuery`] does by default.
+pub struct One;
+
+/// Selects any number of items, which [`SelectQuery::many`] sets.
+pub struct Many;
+
+pub struct SelectQuery<'a, S, H, M = One> {
     prompt: Prompt,
     style: &'a S,
     handler: H,
-    is_many: bool,
+    filter: Option<PlainReader>,
+    matcher: Box<dyn Matcher + 'a>,
+    _marker: PhantomData<M>,
 }
 
 impl<'a, S, H> SelectQuery<'a, S, H> {
@@ -37,106 +51,298 @@ impl<'a, S, H> SelectQuery<'a, S, H> {
             prompt,
             style,
             handler,
-            is_many: false,
+            filter: None,
+            matcher: Box::new(FuzzyMatcher),
+            _marker: PhantomData,
         }
     }
 
-    pub fn many(self) -> Self {
+    /// Lets the user select any number of items with Space, returning all of them.
+    ///
+    /// Items are toggled with Tab instead if the query is [filterable](SelectQuery::filterable).
+    pub fn many(self) -> SelectQuery<'a, S, H, Many> {
+        SelectQuery {
+            prompt: self.prompt,
+            style: self.style,
+            handler: self.handler,
+            filter: self.filter,
+            matcher: self.matcher,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, S, H, M> SelectQuery<'a, S, H, M> {
+    /// Lets the user type on the prompt line to show only the items which fuzzy-match the text,
+    /// with the best matches first.
+    pub fn filterable(self) -> Self {
         Self {
-            is_many: true,
+            filter: Some(PlainReader::default()),
             ..self
         }
     }
+
+    /// Makes the query filterable, where the items are matched by `matcher`
+    /// instead of fuzzy matching.
+    pub fn with_matcher(self, matcher: impl Matcher + 'a) -> Self {
+        Self {
+            matcher: Box::new(matcher),
+            ..self.filterable()
+        }
+    }
 }
 
-impl<'a, S> SelectQuery<'a, S, ListHandler<'a, S>> {
-    pub fn fix_rows(self, rows: usize) -> SelectQuery<'a, S, FixedRowHandler<'a, S>> {
+impl<'a, S, T, M> SelectQuery<'a, S, ListHandler<'a, S, T>, M> {
+    pub fn fix_rows(self, rows: usize) -> SelectQuery<'a, S, FixedRowHandler<'a, S, T>, M> {
         assert!(rows > 0);
         SelectQuery {
             prompt: self.prompt,
             style: self.style,
             handler: FixedRowHandler::from_list_handler(self.handler, rows),
-            is_many: self.is_many,
+            filter: self.filter,
+            matcher: self.matcher,
+            _marker: PhantomData,
         }
     }
 }
 
-impl<'a, S, H> Query for SelectQuery<'a, S, H>
+impl<'a, S, H, T> Query for SelectQuery<'a, S, H, One>
 where
-    S: Styler<Prompt> + Styler<BeginInput> + Styler<EndInput> + Styler<ListItem>,
-    H: SelectHandler<Result = Vec<(usize, String)>>,
+    S: Styler<Prompt>
+        + Styler<EditMode>
+        + Styler<BeginInput>
+        + Styler<EndInput>
+        + Styler<Placeholder>
+        + Styler<Overflow>
+        + Styler<ListItem>,
+    H: SelectHandler<Result = Vec<T>>,
 {
-    type Result = Vec<(usize, String)>;
+    type Result = T;
 
-    fn show_on(self, f: &mut impl Write) -> Result<Self::Result> {
+    fn show_with(
+        self,
+        f: &mut impl Backend,
+        events: &mut impl EventSource,
+    ) -> Result<Self::Result> {
+        let mut result = self.select(f, events, false)?;
+        assert!(result.len() == 1);
+        Ok(result.remove(0))
+    }
+}
+
+impl<'a, S, H, T> Query for SelectQuery<'a, S, H, Many>
+where
+    S: Styler<Prompt>
+        + Styler<EditMode>
+        + Styler<BeginInput>
+        + Styler<EndInput>
+        + Styler<Placeholder>
+        + Styler<Overflow>
+        + Styler<ListItem>,
+    H: SelectHandler<Result = Vec<T>>,
+{
+    type Result = Vec<T>;
+
+    fn show_with(
+        self,
+        f: &mut impl Backend,
+        events: &mut impl EventSource,
+    ) -> Result<Self::Result> {
+        self.select(f, events, true)
+    }
+}
+
+impl<'a, S, H, M> SelectQuery<'a, S, H, M> {
+    fn select<T>(
+        self,
+        f: &mut impl Backend,
+        events: &mut impl EventSource,
+        is_many: bool,
+    ) -> Result<Vec<T>>
+    where
+        S: Styler<Prompt>
+            + Styler<EditMode>
+            + Styler<BeginInput>
+            + Styler<EndInput>
+            + Styler<Placeholder>
+            + Styler<Overflow>
+            + Styler<ListItem>,
+        H: SelectHandler<Result = Vec<T>>,
+    {
         let Self {
             prompt,
             style,
             mut handler,
-            is_many,
+            mut filter,
+            matcher,
+            ..
         } = self;
 
-        queue!(f, Hide)?;
-
-        style.style(f, &prompt)?;
-        writeln!(f)?;
+        if !events.is_interactive() {
+            // Many items are answered as a comma-separated list.
+            let answer = read_answer()?.ok_or(Error::NotATty)?;
+            let answers: Vec<_> = if is_many {
+                answer
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|answer| !answer.is_empty())
+                    .collect()
+            } else if answer.trim().is_empty() {
+                return Err(Error::NotATty);
+            } else {
+                vec![answer.trim()]
+            };
+            for answer in answers {
+                let index = find_item(handler.items(), answer)?;
+                handler.select_at(index);
+            }
+            return Ok(handler.get_result());
+        }
+        let f = &mut TerminalGuard::new(f, events.is_terminal());
 
-        handler.show(f)?;
-        enable_raw_mode()?;
+        queue!(f, Hide)?;
+        if filter.is_none() {
+            style.style(f, &prompt)?;
+            writeln!(f)?;
+        } else {
+            f.enable_bracketed_paste()?;
+        }
+        draw_select(style, f, &prompt, filter.as_ref(), &mut handler)?;
+        f.flush()?;
+        f.enable_raw_mode()?;
         let result = loop {
-            if let Event::Key(event) = event::read()? {
-                match event.code {
+            let redraw = match events.read()? {
+                Event::Key(event) if event.kind != KeyEventKind::Release => match event.code {
+                    _ if is_cancel_key(&event, true) => break Err(Error::Interrupted),
+                    KeyCode::Enter if is_many => break Ok(()),
                     KeyCode::Enter => {
-                        disable_raw_mode()?;
-                        handler.rewind(f)?;
-                        if !is_many {
+                        // Nothing can be selected while the filter matches no item.
+                        if handler.items().iter().any(|item| item.is_cursor) {
+                            handler.toggle();
+                            break Ok(());
+                        }
+                        false
+                    }
+                    // A number selects the item shown at that row right away.
+                    KeyCode::Char('1'..='9') if !is_many && filter.is_none() => {
+                        if handler.on_key(&event) {
                             handler.toggle();
+                            break Ok(());
                         }
-                        break handler.get_result();
+                        false
                     }
-                    KeyCode::Char(' ') if is_many => {
+                    KeyCode::Char(' ') if is_many && filter.is_none() => {
                         handler.toggle();
-                        disable_raw_mode()?;
-                        handler.rewind(f)?;
-                        handler.show(f)?;
-                        f.flush()?;
-                        enable_raw_mode()?;
+                        true
                     }
-                    _ => {
-                        if handler.on_key(&event) {
-                            disable_raw_mode()?;
-                            handler.rewind(f)?;
-                            handler.show(f)?;
-                            f.flush()?;
-                            enable_raw_mode()?;
+                    KeyCode::Tab if is_many && filter.is_some() => {
+                        handler.toggle();
+                        true
+                    }
+                    _ => match &mut filter {
+                        Some(reader) => {
+                            let text = reader.text().to_string();
+                            if reader.on_key(&event) {
+                                if reader.text() != text {
+                                    handler.filter(&*matcher, reader.text());
+                                }
+                                true
+                            } else {
+                                handler.on_key(&event)
+                            }
                         }
+                        None => handler.on_key(&event),
+                    },
+                },
+                Event::Paste(text) => match &mut filter {
+                    Some(reader) => {
+                        let is_pasted = reader.on_paste(&text);
+                        if is_pasted {
+                            handler.filter(&*matcher, reader.text());
+                        }
+                        is_pasted
                     }
-                }
+                    None => false,
+                },
+                _ => false,
+            };
+            if redraw {
+                f.disable_raw_mode()?;
+                draw_select(style, f, &prompt, filter.as_ref(), &mut handler)?;
+                f.flush()?;
+                f.enable_raw_mode()?;
             }
         };
-        assert!(is_many || result.len() == 1);
+        f.disable_raw_mode()?;
 
-        if !is_many {
+        // Goes back to the prompt line, which is left with the answer.
+        queue!(f, Hide)?;
+        if filter.is_none() {
+            handler.rewind(f)?;
             queue!(f, MoveToPreviousLine(1))?;
-            style.style(f, &prompt)?;
+        } else {
+            f.disable_bracketed_paste()?;
+            queue!(f, MoveToColumn(0))?;
+        }
+        style.style(f, &prompt)?;
+        if result.is_ok() && !is_many {
             style.style(f, &BeginInput)?;
-            queue!(f, Print(&result[0].1))?;
+            if let Some(selected) = handler.items().iter().find(|item| item.is_selected) {
+                queue!(f, Print(&selected.item))?;
+            }
             style.style(f, &EndInput)?;
-            writeln!(f)?;
         }
+        queue!(f, Clear(ClearType::UntilNewLine))?;
+        writeln!(f)?;
 
         queue!(f, Clear(ClearType::FromCursorDown), Show)?;
         f.flush()?;
 
-        Ok(result)
+        result.map(|_| handler.get_result())
+    }
+}
+
+/// Draws the list of a select query again.
+///
+/// If the query is filterable, the prompt line with the filter text is also drawn,
+/// and the terminal cursor is left on the filter text.
+fn draw_select<S>(
+    style: &S,
+    f: &mut impl Backend,
+    prompt: &Prompt,
+    filter: Option<&PlainReader>,
+    handler: &mut impl SelectHandler,
+) -> io::Result<()>
+where
+    S: Styler<Prompt>
+        + Styler<EditMode>
+        + Styler<BeginInput>
+        + Styler<EndInput>
+        + Styler<Placeholder>
+        + Styler<Overflow>,
+{
+    match filter {
+        Some(reader) => {
+            let column = redraw_input(style, f, prompt, reader, None)?;
+            handler.show(f)?;
+            queue!(f, Clear(ClearType::FromCursorDown))?;
+            handler.rewind(f)?;
+            queue!(f, MoveToPreviousLine(1), MoveToColumn(column), Show)
+        }
+        None => {
+            handler.rewind(f)?;
+            handler.show(f)
+        }
     }
 }
 
-pub struct DynamicSelectQuery<'a, S, ListGen, HandlerGen> {
+pub struct DynamicSelectQuery<'a, S, ListGen, HandlerGen, R = PlainReader> {
     prompt: Prompt,
     style: &'a S,
     list_gen: ListGen,
     handler_gen: HandlerGen,
+    reader: R,
+    placeholder: Option<Placeholder>,
     wait_message: Option<WaitMessage>,
     debounce: Duration,
 }
@@ -148,11 +354,45 @@ impl<'a, S, ListGen, HandlerGen> DynamicSelectQuery<'a, S, ListGen, HandlerGen>
             style,
             list_gen,
             handler_gen,
+            reader: PlainReader::default(),
+            placeholder: None,
             wait_message: None,
             debounce: Duration::new(0, 0),
         }
     }
 
+    /// Starts with `text` as the filter text, which the user can edit.
+    pub fn with_initial_text(self, text: impl Into<String>) -> Self {
+        Self {
+            reader: PlainReader::with_text(text),
+            ..self
+        }
+    }
+
+    /// Uses vi-like modal editing for the filter text.
+    pub fn vi_mode(self) -> DynamicSelectQuery<'a, S, ListGen, HandlerGen, ViReader> {
+        DynamicSelectQuery {
+            prompt: self.prompt,
+            style: self.style,
+            list_gen: self.list_gen,
+            handler_gen: self.handler_gen,
+            reader: ViReader::with_text(self.reader.text()),
+            placeholder: self.placeholder,
+            wait_message: self.wait_message,
+            debounce: self.debounce,
+        }
+    }
+}
+
+impl<'a, S, ListGen, HandlerGen, R> DynamicSelectQuery<'a, S, ListGen, HandlerGen, R> {
+    /// Sets the text shown while the filter text is empty.
+    pub fn with_placeholder(self, placeholder: impl Into<String>) -> Self {
+        Self {
+            placeholder: Some(Placeholder(placeholder.into())),
+            ..self
+        }
+    }
+
     pub fn wait_message(self, wait_message: impl AsRef<str>) -> Self {
         Self {
             wait_message: Some(WaitMessage(wait_message.as_ref().into())),
@@ -165,15 +405,15 @@ impl<'a, S, ListGen, HandlerGen> DynamicSelectQuery<'a, S, ListGen, HandlerGen>
     }
 }
 
-type FixedRowHandlerGen<'a, 'b, S, T> = Box<dyn FnMut(&[T]) -> FixedRowHandler<'a, S> + 'b>;
+type FixedRowHandlerGen<'a, 'b, S, T> = Box<dyn FnMut(Vec<T>) -> FixedRowHandler<'a, S, T> + 'b>;
 
-impl<'a, S, ListGen, HandlerGen> DynamicSelectQuery<'a, S, ListGen, HandlerGen> {
+impl<'a, S, ListGen, HandlerGen, R> DynamicSelectQuery<'a, S, ListGen, HandlerGen, R> {
     pub fn fix_rows<'b, T>(
         self,
         rows: usize,
-    ) -> DynamicSelectQuery<'a, S, ListGen, FixedRowHandlerGen<'a, 'b, S, T>>
+    ) -> DynamicSelectQuery<'a, S, ListGen, FixedRowHandlerGen<'a, 'b, S, T>, R>
     where
-        HandlerGen: FnMut(&[T]) -> ListHandler<'a, S> + 'b,
+        HandlerGen: FnMut(Vec<T>) -> ListHandler<'a, S, T> + 'b,
     {
         let mut handler_gen = self.handler_gen;
         DynamicSelectQuery {
@@ -183,163 +423,262 @@ impl<'a, S, ListGen, HandlerGen> DynamicSelectQuery<'a, S, ListGen, HandlerGen>
             handler_gen: Box::new(move |list| {
                 FixedRowHandler::from_list_handler(handler_gen(list), rows)
             }),
+            reader: self.reader,
+            placeholder: self.placeholder,
             wait_message: self.wait_message,
             debounce: self.debounce,
         }
     }
 }
 
-impl<'a, S, T, H, ListGen, HandlerGen> Query for DynamicSelectQuery<'a, S, ListGen, HandlerGen>
+impl<'a, S, T, H, ListGen, HandlerGen, R> Query
+    for DynamicSelectQuery<'a, S, ListGen, HandlerGen, R>
 where
     S: Styler<Prompt>
+        + Styler<EditMode>
         + Styler<BeginInput>
         + Styler<EndInput>
+        + Styler<Placeholder>
         + Styler<WaitMessage>
         + Styler<Overflow>,
-    H: SelectHandler<Result = Vec<(usize, String)>> + 'a,
+    H: SelectHandler<Result = Vec<T>> + 'a,
     T: Send + 'static,
     ListGen: (Fn(String) -> Vec<T>) + Send + Sync + 'static,
-    HandlerGen: FnMut(&[T]) -> H + 'a,
+    HandlerGen: FnMut(Vec<T>) -> H + 'a,
+    R: TextReader,
 {
-    type Result = Option<String>;
+    type Result = Option<T>;
 
-    fn show_on(self, f: &mut impl Write) -> Result<Self::Result> {
+    fn show_with(
+        self,
+        f: &mut impl Backend,
+        events: &mut impl EventSource,
+    ) -> Result<Self::Result> {
         let Self {
             prompt,
             style,
             list_gen,
             mut handler_gen,
+            mut reader,
+            placeholder,
             wait_message,
             debounce,
         } = self;
-        let mut reader = PlainReader::default();
+
+        if !events.is_interactive() {
+            // The answer is also the filter text, so only an exact label can match.
+            let answer = read_answer()?.ok_or(Error::NotATty)?;
+            let list = panic::catch_unwind(AssertUnwindSafe(|| list_gen(answer.clone())))
+                .map_err(|payload| Error::ListGenerator(panic_message(&*payload)))?;
+            let mut handler = handler_gen(list);
+            return match handler.items().iter().position(|item| item.item == answer) {
+                Some(index) => {
+                    handler.select_at(index);
+                    Ok(handler.get_result().pop())
+                }
+                None => Err(Error::Invalid(format!("no item matches `{}`", answer))),
+            };
+        }
+        let f = &mut TerminalGuard::new(f, events.is_terminal());
         let list_gen = Arc::new(list_gen);
 
         queue!(f, Hide)?;
 
-        style.style(f, &prompt)?;
-        style.style(f, &BeginInput)?;
-        style.style(f, &EndInput)?;
-        writeln!(f)?;
-        let (tx, rx) = sync_channel::<Vec<T>>(1);
+        let column = redraw_input(style, f, &prompt, &reader, placeholder.as_ref())?;
+        queue!(f, MoveToPreviousLine(1), MoveToColumn(column), Show)?;
+        f.enable_bracketed_paste()?;
+        f.flush()?;
+        let (tx, rx) = sync_channel::<thread::Result<Vec<T>>>(1);
         let spawn_list_gen = |input: String| {
             let tx = tx.clone();
             let list_gen = list_gen.clone();
             thread::spawn(move || {
-                let result = list_gen(input);
-                tx.send(result).unwrap();
+                let result = panic::catch_unwind(AssertUnwindSafe(|| list_gen(input)));
+                // The query might not wait for the list anymore.
+                let _ = tx.send(result);
             });
         };
 
         let mut handler: Option<H> = None;
 
+        // The terminal cursor is kept on the input line between redraws,
+        // so that the user can see where the text is being edited.
         const POLL_DURATION: Duration = Duration::from_millis(10);
         let mut debounce_until = Some(Instant::now());
-        let mut result = loop {
+        let mut generating = 0;
+        let result = loop {
             if let Ok(new_list) = rx.try_recv() {
-                disable_raw_mode()?;
-                if let Some(mut handler) = handler {
-                    handler.rewind(f)?;
-                }
-                queue!(f, MoveToPreviousLine(1))?;
-                style.style(f, &prompt)?;
-                style.style(f, &BeginInput)?;
-                util::trim_print(style, f, &reader.text())?;
-                style.style(f, &EndInput)?;
-                queue!(f, Clear(ClearType::UntilNewLine))?;
-                writeln!(f)?;
-                let mut tmp_handler = handler_gen(&new_list);
+                generating -= 1;
+                let new_list = match new_list {
+                    Ok(new_list) => new_list,
+                    Err(payload) => break Err(Error::ListGenerator(panic_message(&*payload))),
+                };
+                f.disable_raw_mode()?;
+                let column = redraw_input(style, f, &prompt, &reader, placeholder.as_ref())?;
+                let mut tmp_handler = handler_gen(new_list);
                 tmp_handler.show(f)?;
                 queue!(f, Clear(ClearType::FromCursorDown))?;
+                tmp_handler.rewind(f)?;
+                queue!(f, MoveToPreviousLine(1), MoveToColumn(column), Show)?;
                 f.flush()?;
-                enable_raw_mode()?;
+                f.enable_raw_mode()?;
                 handler = Some(tmp_handler);
             }
             if matches!(debounce_until, Some(until) if until < Instant::now()) {
                 debounce_until = None;
                 spawn_list_gen(reader.text().to_string());
-                disable_raw_mode()?;
+                generating += 1;
+                f.disable_raw_mode()?;
                 if let Some(wait_message) = &wait_message {
-                    if let Some(mut handler) = handler {
-                        handler.rewind(f)?;
-                    }
                     handler = None;
-                    queue!(f, MoveToPreviousLine(1))?;
-                    style.style(f, &prompt)?;
-                    style.style(f, &BeginInput)?;
-                    util::trim_print(style, f, &reader.text())?;
-                    style.style(f, &EndInput)?;
-                    queue!(f, Clear(ClearType::UntilNewLine))?;
-                    writeln!(f)?;
+                    let column = redraw_input(style, f, &prompt, &reader, placeholder.as_ref())?;
                     queue!(f, Clear(ClearType::FromCursorDown))?;
                     style.style(f, wait_message)?;
+                    queue!(f, MoveToPreviousLine(1), MoveToColumn(column), Show)?;
                     f.flush()?;
-                    enable_raw_mode()?;
+                    f.enable_raw_mode()?;
                 }
             }
-            if event::poll(POLL_DURATION)? {
-                if let Event::Key(event) = event::read()? {
-                    let redraw = match event.code {
+            if !events.is_terminal() && (generating > 0 || debounce_until.is_some()) {
+                thread::sleep(POLL_DURATION);
+                continue;
+            }
+            if events.poll(POLL_DURATION)? {
+                let redraw = match events.read()? {
+                    Event::Key(event) if event.kind != KeyEventKind::Release => match event.code {
+                        _ if is_cancel_key(&event, false) => break Err(Error::Interrupted),
                         KeyCode::Enter => {
                             if let Some(mut handler) = handler {
                                 handler.toggle();
-                                disable_raw_mode()?;
-                                handler.rewind(f)?;
-                                break handler.get_result();
+                                f.disable_raw_mode()?;
+                                break Ok(handler);
                             } else {
                                 false
                             }
                         }
                         _ => {
-                            if reader.on_key(&event) {
+                            if is_cancel_key(&event, !reader.handles_esc()) {
+                                // Esc cancels only if the reader does not use it.
+                                break Err(Error::Interrupted);
+                            } else if reader.on_key(&event) {
                                 debounce_until = Some(Instant::now() + debounce);
                                 true
                             } else {
                                 handler.as_mut().map(|h| h.on_key(&event)).unwrap_or(false)
                             }
                         }
-                    };
-                    if redraw {
-                        disable_raw_mode()?;
-                        if let Some(handler) = &mut handler {
-                            handler.rewind(f)?;
+                    },
+                    Event::Paste(text) => {
+                        let is_pasted = reader.on_paste(&text);
+                        if is_pasted {
+                            debounce_until = Some(Instant::now() + debounce);
                         }
+                        is_pasted
+                    }
+                    _ => false,
+                };
+                if redraw {
+                    f.disable_raw_mode()?;
+                    let column = redraw_input(style, f, &prompt, &reader, placeholder.as_ref())?;
+                    if let Some(handler) = &mut handler {
+                        handler.show(f)?;
+                        queue!(f, Clear(ClearType::FromCursorDown))?;
+                        handler.rewind(f)?;
+                        queue!(f, MoveToPreviousLine(1))?;
+                    } else if let Some(wait_message) = &wait_message {
+                        queue!(f, Clear(ClearType::FromCursorDown))?;
+                        style.style(f, wait_message)?;
+                        queue!(f, MoveToPreviousLine(1))?;
+                    } else {
                         queue!(f, MoveToPreviousLine(1))?;
-                        style.style(f, &prompt)?;
-                        style.style(f, &BeginInput)?;
-                        util::trim_print(style, f, &reader.text())?;
-                        style.style(f, &EndInput)?;
-                        queue!(f, Clear(ClearType::UntilNewLine))?;
-                        writeln!(f)?;
-                        if let Some(handler) = &mut handler {
-                            handler.show(f)?;
-                            queue!(f, Clear(ClearType::FromCursorDown))?;
-                        } else if let Some(wait_message) = &wait_message {
-                            queue!(f, Clear(ClearType::FromCursorDown))?;
-                            style.style(f, wait_message)?;
-                        }
-                        f.flush()?;
-                        enable_raw_mode()?;
                     }
+                    queue!(f, MoveToColumn(column), Show)?;
+                    f.flush()?;
+                    f.enable_raw_mode()?;
                 }
             }
         };
-        disable_raw_mode()?;
-        assert!(result.len() <= 1);
+        f.disable_raw_mode()?;
+        f.disable_bracketed_paste()?;
 
-        queue!(f, MoveToPreviousLine(1), Clear(ClearType::CurrentLine))?;
+        queue!(f, Hide, MoveToColumn(0), Clear(ClearType::CurrentLine))?;
         style.style(f, &prompt)?;
         style.style(f, &BeginInput)?;
-        let result = (!result.is_empty()).then(|| result.remove(0).1);
-        if let Some(item) = &result {
-            util::trim_print(style, f, &item)?;
+        let selected = match &result {
+            Ok(handler) => handler.items().iter().find(|item| item.is_selected),
+            Err(_) => None,
+        };
+        if let Some(selected) = selected {
+            util::trim_print(style, f, &selected.item)?;
         }
         style.style(f, &EndInput)?;
         queue!(f, Clear(ClearType::UntilNewLine))?;
         writeln!(f)?;
 
         queue!(f, Clear(ClearType::FromCursorDown), Show)?;
+        f.flush()?;
+
+        result.map(|handler| handler.get_result().pop())
+    }
+}
 
-        Ok(result)
+/// Finds the item answered without a terminal, by its exact label
+/// or by its number counted from 1, as the number keys do.
+fn find_item(items: &[ListItem], answer: &str) -> Result<usize> {
+    items
+        .iter()
+        .position(|item| item.item == answer)
+        .or_else(|| {
+            answer
+                .parse::<usize>()
+                .ok()
+                .filter(|number| (1..=items.len()).contains(number))
+                .map(|number| number - 1)
+        })
+        .ok_or_else(|| Error::Invalid(format!("no item matches `{}`", answer)))
+}
+
+/// Returns the message of a panic, if it has one.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => payload
+            .downcast_ref::<String>()
+            .cloned()
+            .unwrap_or_default(),
+    }
+}
+
+/// Redraws the prompt line from its beginning and moves to the next line.
+///
+/// Returns the column where the text cursor should be placed.
+fn redraw_input<S>(
+    style: &S,
+    f: &mut impl Backend,
+    prompt: &Prompt,
+    reader: &impl TextReader,
+    placeholder: Option<&Placeholder>,
+) -> io::Result<u16>
+where
+    S: Styler<Prompt>
+        + Styler<EditMode>
+        + Styler<BeginInput>
+        + Styler<EndInput>
+        + Styler<Placeholder>
+        + Styler<Overflow>,
+{
+    queue!(f, Hide, MoveToColumn(0))?;
+    style.style(f, prompt)?;
+    if let Some(mode) = reader.edit_mode() {
+        style.style(f, &mode)?;
+    }
+    style.style(f, &BeginInput)?;
+    let column = util::trim_print_with_cursor(style, f, reader.text(), reader.cursor())?;
+    if let Some(placeholder) = placeholder.filter(|_| reader.input().is_empty()) {
+        style.style(f, placeholder)?;
     }
+    style.style(f, &EndInput)?;
+    queue!(f, Clear(ClearType::UntilNewLine))?;
+    writeln!(f)?;
+    Ok(column)
 }
@@ -0,0 +1,348 @@
+use std::io::{self, Write};
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::terminal::Backend;
+
+/// A terminal screen kept in memory, which interprets the output of a query into cells.
+///
+/// It lets the frame drawn by a query be compared with the expected one,
+/// without a real terminal:
+///
+/// ```
+/// # use crossterm::event::{KeyCode, KeyEvent};
+/// # use muroba::{event::ScriptedEvents, query::{Query, QueryBuilder}, screen::VirtualScreen};
+/// let mut screen = VirtualScreen::new(20, 5);
+/// let mut events = ScriptedEvents::new(vec![KeyEvent::from(KeyCode::Down)]);
+/// // The query fails as the script ends, leaving the last frame on the screen.
+/// let _ = QueryBuilder::default()
+///     .with_prompt("Pick")
+///     .select(&["a", "b"])
+///     .show_with(&mut screen, &mut events);
+/// assert_eq!(screen.contents(), "? Pick\n  a\n> b");
+/// ```
+///
+/// Line feeds also return the cursor to the first column, as a terminal does out of raw mode.
+/// Colors and other attributes are ignored.
+pub struct VirtualScreen {
+    width: u16,
+    height: u16,
+    /// The grapheme cluster in each cell, where the cell after a wide one is empty.
+    cells: Vec<Vec<String>>,
+    column: u16,
+    row: u16,
+    is_cursor_visible: bool,
+    /// The cell which the next zero-width character is joined to.
+    last_cell: Option<(u16, u16)>,
+    /// The bytes which do not make a whole character or escape sequence yet.
+    pending: Vec<u8>,
+}
+
+impl VirtualScreen {
+    pub fn new(width: u16, height: u16) -> Self {
+        assert!(width > 0 && height > 0);
+        Self {
+            width,
+            height,
+            cells: vec![blank_row(width); height as usize],
+            column: 0,
+            row: 0,
+            is_cursor_visible: true,
+            last_cell: None,
+            pending: vec![],
+        }
+    }
+
+    /// Returns the text of each row, without trailing spaces.
+    pub fn rows(&self) -> Vec<String> {
+        self.cells
+            .iter()
+            .map(|row| row.concat().trim_end().to_string())
+            .collect()
+    }
+
+    /// Returns the text on the screen, where rows are separated by newlines.
+    ///
+    /// Empty rows at the bottom are left out.
+    pub fn contents(&self) -> String {
+        let rows = self.rows();
+        let len = rows
+            .iter()
+            .rposition(|row| !row.is_empty())
+            .map_or(0, |i| i + 1);
+        rows[..len].join("\n")
+    }
+
+    /// Changes the size of the screen, keeping the cells which still fit.
+    ///
+    /// Rows are dropped from the top as long as the cursor row is below them,
+    /// like a terminal which keeps the cursor in view.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        assert!(width > 0 && height > 0);
+        if (width, height) == (self.width, self.height) {
+            return;
+        }
+        for row in &mut self.cells {
+            row.resize(width as usize, " ".to_string());
+            // A wide character cut at the new edge does not fit anymore.
+            if row[width as usize - 1].width() > 1 {
+                row[width as usize - 1] = " ".to_string();
+            }
+        }
+        while self.cells.len() > height as usize {
+            if self.row > 0 {
+                self.cells.remove(0);
+                self.row -= 1;
+            } else {
+                self.cells.pop();
+            }
+        }
+        self.cells.resize(height as usize, blank_row(width));
+        self.width = width;
+        self.height = height;
+        self.column = self.column.min(width);
+        self.last_cell = None;
+    }
+
+    pub fn is_cursor_visible(&self) -> bool {
+        self.is_cursor_visible
+    }
+
+    /// Interprets as much of the pending output as possible.
+    fn process(&mut self) {
+        let mut start = 0;
+        while start < self.pending.len() {
+            let consumed = match self.pending[start] {
+                0x1b => self.escape(start),
+                b'\n' => {
+                    self.column = 0;
+                    self.line_feed();
+                    Some(1)
+                }
+                b'\r' => {
+                    self.move_to(0, self.row);
+                    Some(1)
+                }
+                0x08 => {
+                    self.move_to(self.column.saturating_sub(1), self.row);
+                    Some(1)
+                }
+                byte if byte < 0x20 || byte == 0x7f => Some(1),
+                _ => self.character(start),
+            };
+            match consumed {
+                Some(len) => start += len,
+                None => break,
+            }
+        }
+        self.pending.drain(..start);
+    }
+
+    /// Interprets the character at `start`, or returns `None` if it is incomplete.
+    fn character(&mut self, start: usize) -> Option<usize> {
+        let rest = &self.pending[start..];
+        let len = match rest[0] {
+            0xf0..=0xff => 4,
+            0xe0..=0xef => 3,
+            0xc0..=0xdf => 2,
+            _ => 1,
+        };
+        if rest.len() < len {
+            return None;
+        }
+        let c = std::str::from_utf8(&rest[..len])
+            .ok()
+            .and_then(|s| s.chars().next())
+            .unwrap_or(char::REPLACEMENT_CHARACTER);
+        self.print(c);
+        Some(len)
+    }
+
+    fn print(&mut self, c: char) {
+        // A character which continues the grapheme cluster of the last cell is joined to it.
+        if let Some((column, row)) = self.last_cell {
+            let cell = &mut self.cells[row as usize][column as usize];
+            let mut joined = cell.clone();
+            joined.push(c);
+            if joined.graphemes(true).count() == 1 {
+                *cell = joined;
+                return;
+            }
+        }
+
+        let mut buffer = [0; 4];
+        let width = c.encode_utf8(&mut buffer).width() as u16;
+        if width == 0 {
+            return;
+        }
+        if self.column + width > self.width {
+            self.column = 0;
+            self.line_feed();
+        }
+        self.clear_wide_cell(self.column);
+        if width == 2 {
+            self.clear_wide_cell(self.column + 1);
+        }
+        let row = &mut self.cells[self.row as usize];
+        row[self.column as usize] = c.to_string();
+        if width == 2 {
+            row[self.column as usize + 1] = String::new();
+        }
+        self.last_cell = Some((self.column, self.row));
+        // The cursor stays past the last column until the next character wraps.
+        self.column += width;
+    }
+
+    /// Blanks out the other half of the wide character at `column`, which is being overwritten.
+    fn clear_wide_cell(&mut self, column: u16) {
+        let row = &mut self.cells[self.row as usize];
+        let column = column as usize;
+        if column >= row.len() {
+            return;
+        }
+        if row[column].is_empty() && column > 0 {
+            row[column - 1] = " ".to_string();
+        }
+        if row.get(column + 1).is_some_and(String::is_empty) {
+            row[column + 1] = " ".to_string();
+        }
+    }
+
+    /// Interprets the escape sequence at `start`, or returns `None` if it is incomplete.
+    fn escape(&mut self, start: usize) -> Option<usize> {
+        let rest = &self.pending[start..];
+        match rest.get(1)? {
+            b'[' => {}
+            _ => return Some(2),
+        }
+        let end = rest[2..].iter().position(|b| (0x40..=0x7e).contains(b))? + 2;
+        let params = String::from_utf8_lossy(&rest[2..end]).into_owned();
+        let action = rest[end];
+        self.control(&params, action);
+        Some(end + 1)
+    }
+
+    /// Runs a control sequence.
+    fn control(&mut self, params: &str, action: u8) {
+        if let Some(private) = params.strip_prefix('?') {
+            if private == "25" {
+                match action {
+                    b'h' => self.is_cursor_visible = true,
+                    b'l' => self.is_cursor_visible = false,
+                    _ => {}
+                }
+            }
+            return;
+        }
+
+        let args: Vec<u16> = params
+            .split(';')
+            .map(|arg| arg.parse().unwrap_or(0))
+            .collect();
+        let arg = |i: usize| args.get(i).copied().unwrap_or(0);
+        let count = arg(0).max(1);
+        let (column, row) = (self.column.min(self.width - 1), self.row);
+        match action {
+            b'A' => self.move_to(column, row.saturating_sub(count)),
+            b'B' => self.move_to(column, row.saturating_add(count)),
+            b'C' => self.move_to(column.saturating_add(count), row),
+            b'D' => self.move_to(column.saturating_sub(count), row),
+            b'E' => self.move_to(0, row.saturating_add(count)),
+            b'F' => self.move_to(0, row.saturating_sub(count)),
+            b'G' => self.move_to(count - 1, row),
+            b'H' | b'f' => self.move_to(arg(1).max(1) - 1, count - 1),
+            b'J' => match arg(0) {
+                0 => {
+                    self.clear_line(self.column, self.width);
+                    self.clear_rows(row + 1, self.height);
+                }
+                1 => {
+                    self.clear_rows(0, row);
+                    self.clear_line(0, self.column + 1);
+                }
+                _ => self.clear_rows(0, self.height),
+            },
+            b'K' => match arg(0) {
+                0 => self.clear_line(self.column, self.width),
+                1 => self.clear_line(0, self.column + 1),
+                _ => self.clear_line(0, self.width),
+            },
+            b'S' => {
+                for _ in 0..count {
+                    self.scroll_up();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn move_to(&mut self, column: u16, row: u16) {
+        self.column = column.min(self.width - 1);
+        self.row = row.min(self.height - 1);
+        self.last_cell = None;
+    }
+
+    fn line_feed(&mut self) {
+        if self.row + 1 < self.height {
+            self.row += 1;
+        } else {
+            self.scroll_up();
+        }
+        self.last_cell = None;
+    }
+
+    fn scroll_up(&mut self) {
+        self.cells.remove(0);
+        self.cells.push(blank_row(self.width));
+        self.last_cell = None;
+    }
+
+    /// Clears the cells of the cursor row from `start` to before `end`.
+    fn clear_line(&mut self, start: u16, end: u16) {
+        let end = end.min(self.width);
+        if start >= end {
+            return;
+        }
+        self.clear_wide_cell(start);
+        self.clear_wide_cell(end - 1);
+        for cell in &mut self.cells[self.row as usize][start as usize..end as usize] {
+            *cell = " ".to_string();
+        }
+        self.last_cell = None;
+    }
+
+    /// Clears the rows from `start` to before `end`.
+    fn clear_rows(&mut self, start: u16, end: u16) {
+        for row in start..end.min(self.height) {
+            self.cells[row as usize] = blank_row(self.width);
+        }
+        self.last_cell = None;
+    }
+}
+
+fn blank_row(width: u16) -> Vec<String> {
+    vec![" ".to_string(); width as usize]
+}
+
+impl Write for VirtualScreen {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        self.process();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Backend for VirtualScreen {
+    fn size(&mut self) -> io::Result<(u16, u16)> {
+        Ok((self.width, self.height))
+    }
+
+    fn cursor_position(&mut self) -> io::Result<(u16, u16)> {
+        Ok((self.column.min(self.width - 1), self.row))
+    }
+}
@@ -1,23 +1,23 @@
-use std::io::Write;
+use std::io::Result;
 
 use crossterm::{
     execute, queue,
     style::{
-        Attribute, Color, Colorize, Print, PrintStyledContent, ResetColor, SetAttribute,
-        SetForegroundColor, Styler as TermStyler,
+        Attribute, Color, Print, PrintStyledContent, ResetColor, SetAttribute, SetForegroundColor,
+        Stylize,
     },
 };
 
-use crate::{item::*, util, Result};
+use crate::{item::*, terminal::Backend, util};
 
 pub struct DefaultStyle;
 
 pub trait Styler<I> {
-    fn style(&self, f: &mut impl Write, item: &I) -> Result<()>;
+    fn style(&self, f: &mut impl Backend, item: &I) -> Result<()>;
 }
 
 impl Styler<Prompt> for DefaultStyle {
-    fn style(&self, f: &mut impl Write, Prompt(prompt): &Prompt) -> Result<()> {
+    fn style(&self, f: &mut impl Backend, Prompt(prompt): &Prompt) -> Result<()> {
         queue!(
             f,
             PrintStyledContent("?".green()),
@@ -29,7 +29,7 @@ impl Styler<Prompt> for DefaultStyle {
 }
 
 impl Styler<BeginInput> for DefaultStyle {
-    fn style(&self, f: &mut impl Write, _: &BeginInput) -> Result<()> {
+    fn style(&self, f: &mut impl Backend, _: &BeginInput) -> Result<()> {
         execute!(
             f,
             PrintStyledContent(" > ".dark_grey()),
@@ -39,13 +39,67 @@ impl Styler<BeginInput> for DefaultStyle {
 }
 
 impl Styler<EndInput> for DefaultStyle {
-    fn style(&self, f: &mut impl Write, _: &EndInput) -> Result<()> {
+    fn style(&self, f: &mut impl Backend, _: &EndInput) -> Result<()> {
         queue!(f, ResetColor,)
     }
 }
 
+impl Styler<DefaultValue> for DefaultStyle {
+    fn style(&self, f: &mut impl Backend, DefaultValue(default): &DefaultValue) -> Result<()> {
+        queue!(f, PrintStyledContent(format!(" ({})", default).dark_grey()))
+    }
+}
+
+impl Styler<EditMode> for DefaultStyle {
+    fn style(&self, f: &mut impl Backend, mode: &EditMode) -> Result<()> {
+        match mode {
+            EditMode::Insert => queue!(f, PrintStyledContent(" [I]".dark_grey())),
+            EditMode::Normal => queue!(f, PrintStyledContent(" [N]".yellow())),
+        }
+    }
+}
+
+impl Styler<HistorySearch> for DefaultStyle {
+    fn style(&self, f: &mut impl Backend, HistorySearch(query): &HistorySearch) -> Result<()> {
+        queue!(
+            f,
+            PrintStyledContent(" (search: ".dark_grey()),
+            Print(query),
+            PrintStyledContent(")".dark_grey()),
+        )
+    }
+}
+
+impl Styler<Placeholder> for DefaultStyle {
+    fn style(&self, f: &mut impl Backend, Placeholder(placeholder): &Placeholder) -> Result<()> {
+        queue!(f, SetForegroundColor(Color::DarkGrey))?;
+        util::truncate_print(f, placeholder)?;
+        queue!(f, ResetColor)
+    }
+}
+
+impl Styler<Suggestion> for DefaultStyle {
+    fn style(&self, f: &mut impl Backend, Suggestion(suggestion): &Suggestion) -> Result<()> {
+        queue!(f, SetForegroundColor(Color::DarkGrey))?;
+        util::truncate_print(f, suggestion)?;
+        queue!(f, ResetColor)
+    }
+}
+
+impl Styler<ValidationError> for DefaultStyle {
+    fn style(
+        &self,
+        f: &mut impl Backend,
+        ValidationError(message): &ValidationError,
+    ) -> Result<()> {
+        queue!(f, SetForegroundColor(Color::Red), Print("✗ "))?;
+        util::truncate_print(f, message)?;
+        queue!(f, ResetColor)
+    }
+}
+
 impl Styler<ConfirmChoice> for DefaultStyle {
-    fn style(&self, f: &mut impl Write, ConfirmChoice(default): &ConfirmChoice) -> Result<()> {
+    fn style(&self, f: &mut impl Backend, ConfirmChoice(default): &ConfirmChoice) -> Result<()> {
         match default {
             None => {
                 queue!(f, Print(" [y/n]"))
@@ -73,7 +127,7 @@ impl Styler<ConfirmChoice> for DefaultStyle {
 }
 
 impl Styler<ListItem> for DefaultStyle {
-    fn style(&self, f: &mut impl Write, list_item: &ListItem) -> Result<()> {
+    fn style(&self, f: &mut impl Backend, list_item: &ListItem) -> Result<()> {
         if list_item.is_cursor {
             queue!(f, SetForegroundColor(Color::Blue), Print("> "),)?;
             if list_item.is_selected {
@@ -85,7 +139,22 @@ impl Styler<ListItem> for DefaultStyle {
             queue!(f, Print("  "),)?;
         }
 
-        util::trim_print(self, f, &list_item.item)?;
+        // The characters which match the filter are underlined.
+        let item = &list_item.item;
+        let shown = util::trim_with_overflow(self, f, item)?;
+        let trimmed = item[..item.len() - shown.len()].chars().count();
+        for (i, c) in shown.chars().enumerate() {
+            if list_item.matches.binary_search(&(trimmed + i)).is_ok() {
+                queue!(
+                    f,
+                    SetAttribute(Attribute::Underlined),
+                    Print(c),
+                    SetAttribute(Attribute::NoUnderline),
+                )?;
+            } else {
+                queue!(f, Print(c))?;
+            }
+        }
         queue!(f, ResetColor, SetAttribute(Attribute::Reset))?;
 
         Ok(())
@@ -93,7 +162,7 @@ impl Styler<ListItem> for DefaultStyle {
 }
 
 impl Styler<WaitMessage> for DefaultStyle {
-    fn style(&self, f: &mut impl Write, WaitMessage(message): &WaitMessage) -> Result<()> {
+    fn style(&self, f: &mut impl Backend, WaitMessage(message): &WaitMessage) -> Result<()> {
         queue!(
             f,
             PrintStyledContent(message.as_str().dark_grey().italic()),
@@ -104,7 +173,7 @@ impl Styler<WaitMessage> for DefaultStyle {
 }
 
 impl Styler<Overflow> for DefaultStyle {
-    fn style(&self, f: &mut impl Write, _: &Overflow) -> Result<()> {
+    fn style(&self, f: &mut impl Backend, _: &Overflow) -> Result<()> {
         queue!(f, Print("…"))
     }
 }
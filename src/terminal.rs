@@ -0,0 +1,189 @@
+use std::{
+    io::{self, stderr, stdout, Write},
+    panic,
+};
+
+use crossterm::{
+    cursor::{self, MoveTo, Show},
+    event::{DisableBracketedPaste, EnableBracketedPaste},
+    execute, queue, terminal,
+    tty::IsTty,
+};
+
+use crate::screen::VirtualScreen;
+
+/// Where a query draws, which also tells the size of the screen and where the cursor is.
+///
+/// [`TerminalBackend`] draws on the terminal, while [`VirtualScreen`] keeps the screen in memory.
+pub trait Backend: Write {
+    /// Returns the number of columns and rows of the screen.
+    fn size(&mut self) -> io::Result<(u16, u16)>;
+    /// Returns the column and row of the cursor, starting from zero.
+    fn cursor_position(&mut self) -> io::Result<(u16, u16)>;
+}
+
+/// Draws on a writer, following the cursor position through the output.
+///
+/// The terminal is asked where the cursor is only once, before the first output,
+/// and the position is then followed from the output on a screen which is resized
+/// along with the terminal. Asking again on every redraw would be slow, and the answer
+/// would be mixed with the keys typed meanwhile.
+///
+/// The terminal is asked through stdout, so the cursor is assumed to be at the beginning
+/// of a line if stdout is not a terminal.
+pub struct TerminalBackend<W> {
+    f: W,
+    /// The screen which follows the output, created before the first output.
+    screen: Option<VirtualScreen>,
+}
+
+impl<W: Write> TerminalBackend<W> {
+    pub fn new(f: W) -> Self {
+        Self { f, screen: None }
+    }
+
+    /// Returns the screen which follows the output, with the current size of the terminal.
+    fn screen(&mut self) -> io::Result<&mut VirtualScreen> {
+        let (width, height) = terminal::size().unwrap_or((80, 24));
+        let (width, height) = (width.max(1), height.max(1));
+        let screen = match self.screen.take() {
+            Some(mut screen) => {
+                screen.resize(width, height);
+                screen
+            }
+            None => {
+                let mut screen = VirtualScreen::new(width, height);
+                if stdout().is_tty() {
+                    if let Ok((column, row)) = cursor::position() {
+                        queue!(screen, MoveTo(column, row))?;
+                    }
+                }
+                screen
+            }
+        };
+        Ok(self.screen.insert(screen))
+    }
+}
+
+impl<W: Write> Write for TerminalBackend<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.screen()?;
+        let len = self.f.write(buf)?;
+        if let Some(screen) = &mut self.screen {
+            screen.write_all(&buf[..len])?;
+        }
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.f.flush()
+    }
+}
+
+impl<W: Write> Backend for TerminalBackend<W> {
+    fn size(&mut self) -> io::Result<(u16, u16)> {
+        self.screen()?.size()
+    }
+
+    fn cursor_position(&mut self) -> io::Result<(u16, u16)> {
+        self.screen()?.cursor_position()
+    }
+}
+
+/// Sets up the terminal for a query and restores it when dropped.
+///
+/// Output is written through the guard, which forwards it to the wrapped writer.
+/// When the guard is dropped, raw mode and bracketed paste are disabled and the cursor
+/// is shown again, even if the query returns early with an error or panics.
+pub struct TerminalGuard<'a, W: Write> {
+    f: &'a mut W,
+    uses_raw_mode: bool,
+    is_raw: bool,
+    is_pasting: bool,
+}
+
+impl<'a, W: Write> TerminalGuard<'a, W> {
+    /// Wraps `f`, leaving raw mode alone unless `uses_raw_mode` is `true`,
+    /// as a query which does not read the terminal does not need it.
+    pub fn new(f: &'a mut W, uses_raw_mode: bool) -> Self {
+        Self {
+            f,
+            uses_raw_mode,
+            is_raw: false,
+            is_pasting: false,
+        }
+    }
+
+    pub fn enable_raw_mode(&mut self) -> io::Result<()> {
+        if self.uses_raw_mode {
+            terminal::enable_raw_mode()?;
+            self.is_raw = true;
+        }
+        Ok(())
+    }
+
+    pub fn disable_raw_mode(&mut self) -> io::Result<()> {
+        if self.is_raw {
+            terminal::disable_raw_mode()?;
+            self.is_raw = false;
+        }
+        Ok(())
+    }
+
+    pub fn enable_bracketed_paste(&mut self) -> io::Result<()> {
+        queue!(self.f, EnableBracketedPaste)?;
+        self.is_pasting = true;
+        Ok(())
+    }
+
+    pub fn disable_bracketed_paste(&mut self) -> io::Result<()> {
+        if self.is_pasting {
+            queue!(self.f, DisableBracketedPaste)?;
+            self.is_pasting = false;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for TerminalGuard<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.f.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.f.flush()
+    }
+}
+
+impl<W: Backend> Backend for TerminalGuard<'_, W> {
+    fn size(&mut self) -> io::Result<(u16, u16)> {
+        self.f.size()
+    }
+
+    fn cursor_position(&mut self) -> io::Result<(u16, u16)> {
+        self.f.cursor_position()
+    }
+}
+
+impl<W: Write> Drop for TerminalGuard<'_, W> {
+    fn drop(&mut self) {
+        let _ = self.disable_raw_mode();
+        let _ = self.disable_bracketed_paste();
+        let _ = queue!(self.f, Show);
+        let _ = self.f.flush();
+    }
+}
+
+/// Installs a panic hook which restores the terminal before the panic message is printed.
+///
+/// Without it, the message is printed while the terminal is still in raw mode,
+/// as [`TerminalGuard`] restores the terminal only after the panic hook has run.
+/// The previous hook is called afterwards.
+pub fn install_panic_hook() {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = terminal::disable_raw_mode();
+        let _ = execute!(stderr(), DisableBracketedPaste, Show);
+        hook(info);
+    }));
+}
@@ -1,45 +1,122 @@
-use std::io::Write;
+use std::io::Result;
 
-use crossterm::{cursor, queue, style::Print, terminal};
+use crossterm::{cursor::MoveToPreviousLine, queue, style::Print};
+use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
-use crate::{item::Overflow, style::Styler, Result};
+use crate::{item::Overflow, style::Styler, terminal::Backend};
 
-pub fn trim_print<S>(style: &S, f: &mut impl Write, content: &str) -> Result<()>
+pub fn trim_print<S>(style: &S, f: &mut impl Backend, content: &str) -> Result<()>
 where
     S: Styler<Overflow>,
 {
-    let mut str = trim_overflow(content)?;
+    let str = trim_with_overflow(style, f, content)?;
+    queue!(f, Print(str))
+}
+
+/// Prints the overflow mark if `content` does not fit in the current line,
+/// and returns the end of `content` which fits after the mark.
+pub fn trim_with_overflow<'c, S>(
+    style: &S,
+    f: &mut impl Backend,
+    content: &'c str,
+) -> Result<&'c str>
+where
+    S: Styler<Overflow>,
+{
+    let mut str = trim_overflow(f, content)?;
     if str.len() < content.len() {
         style.style(f, &Overflow)?;
-        str = trim_overflow(str)?;
+        str = trim_overflow(f, str)?;
     }
-    queue!(f, Print(str))
+    Ok(str)
+}
+
+/// Prints `content` with the text cursor at byte offset `at`.
+///
+/// The text before the cursor is trimmed like [`trim_print`], and the text after the cursor
+/// is cut at the end of the line. Returns the column where the cursor should be placed.
+pub fn trim_print_with_cursor<S>(
+    style: &S,
+    f: &mut impl Backend,
+    content: &str,
+    at: usize,
+) -> Result<u16>
+where
+    S: Styler<Overflow>,
+{
+    trim_print(style, f, &content[..at])?;
+    let (column, _) = f.cursor_position()?;
+    truncate_print(f, &content[at..])?;
+    Ok(column)
 }
 
-fn trim_overflow(content: &str) -> Result<&str> {
-    let remaining = remaining()?;
-    let mut indices = content.char_indices();
-    let mut step_bound = content.chars().count();
-    while step_bound > 0 {
-        let step = step_bound / 2;
-        let mut moved = indices.clone();
-        for _ in 0..step {
-            moved.next();
+/// Prints the beginning of `content` which fits in the current line.
+pub fn truncate_print(f: &mut impl Backend, content: &str) -> Result<()> {
+    let content = truncate_overflow(f, content)?;
+    queue!(f, Print(content))
+}
+
+/// Moves back to the first column of the row which is `rows` rows above,
+/// where something drawn earlier started, and resets `rows` to zero.
+pub fn rewind(f: &mut impl Backend, rows: &mut u16) -> Result<()> {
+    if *rows > 0 {
+        queue!(f, MoveToPreviousLine(*rows))?;
+        *rows = 0;
+    }
+    Ok(())
+}
+
+/// Splits `content` into rows, breaking at newlines and where a line is wider than `width`.
+///
+/// Each row is returned with its byte offset in `content`, without the newline.
+pub fn wrap(content: &str, width: usize) -> Vec<(usize, &str)> {
+    let mut rows = vec![];
+    let mut line_start = 0;
+    for line in content.split('\n') {
+        let mut start = 0;
+        let mut row_width = 0;
+        for (i, grapheme) in line.grapheme_indices(true) {
+            let grapheme_width = grapheme.width_cjk();
+            if row_width + grapheme_width > width && i > start {
+                rows.push((line_start + start, &line[start..i]));
+                start = i;
+                row_width = 0;
+            }
+            row_width += grapheme_width;
         }
-        if matches!(moved.next(), Some((i, _)) if content[i..].width_cjk() > remaining) {
-            indices = moved;
-            step_bound -= step + 1;
-        } else {
-            step_bound = step;
+        rows.push((line_start + start, &line[start..]));
+        line_start += line.len() + 1;
+    }
+    rows
+}
+
+fn truncate_overflow<'c>(f: &mut impl Backend, content: &'c str) -> Result<&'c str> {
+    let remaining = remaining(f)?;
+    let mut width = 0;
+    for (i, grapheme) in content.grapheme_indices(true) {
+        width += grapheme.width_cjk();
+        if width > remaining {
+            return Ok(&content[..i]);
         }
     }
+    Ok(content)
+}
 
-    Ok(indices.next().map_or("", |(i, _)| &content[i..]))
+fn trim_overflow<'c>(f: &mut impl Backend, content: &'c str) -> Result<&'c str> {
+    let remaining = remaining(f)?;
+    let mut width = 0;
+    for (i, grapheme) in content.grapheme_indices(true).rev() {
+        width += grapheme.width_cjk();
+        if width > remaining {
+            return Ok(&content[i + grapheme.len()..]);
+        }
+    }
+    Ok(content)
 }
 
-fn remaining() -> Result<usize> {
-    let (x, _) = cursor::position()?;
-    let (width, _) = terminal::size()?;
-    Ok((width - x) as usize)
+fn remaining(f: &mut impl Backend) -> Result<usize> {
+    let (x, _) = f.cursor_position()?;
+    let (width, _) = f.size()?;
+    Ok(width.saturating_sub(x) as usize)
 }
@@ -0,0 +1,62 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use muroba::{
+    event::ScriptedEvents,
+    query::{Query, QueryBuilder},
+    screen::VirtualScreen,
+    Error,
+};
+
+#[test]
+fn confirm_reads_scripted_keys() {
+    let mut events = ScriptedEvents::new(vec![
+        KeyEvent::from(KeyCode::Char('x')),
+        KeyEvent::from(KeyCode::Char('n')),
+    ]);
+    let result = QueryBuilder::default()
+        .confirm(Some(true))
+        .show_with(&mut VirtualScreen::new(20, 4), &mut events);
+    assert!(matches!(result, Ok(false)));
+    assert!(events.is_empty());
+}
+
+#[test]
+fn confirm_is_cancelled_by_scripted_ctrl_c() {
+    let mut events = ScriptedEvents::new(vec![KeyEvent::new(
+        KeyCode::Char('c'),
+        KeyModifiers::CONTROL,
+    )]);
+    let result = QueryBuilder::default()
+        .confirm(None)
+        .show_with(&mut VirtualScreen::new(20, 4), &mut events);
+    assert!(matches!(result, Err(Error::Interrupted)));
+}
+
+#[test]
+fn ended_script_fails_instead_of_waiting() {
+    let mut events = ScriptedEvents::new(vec![KeyEvent::from(KeyCode::Enter)]);
+    let result = QueryBuilder::default()
+        .confirm(None)
+        .show_with(&mut VirtualScreen::new(20, 4), &mut events);
+    assert!(matches!(result, Err(Error::Io(_))));
+}
+
+#[test]
+fn vi_input_keeps_text_on_repeated_esc() {
+    let mut events = ScriptedEvents::new(
+        [
+            KeyCode::Char('h'),
+            KeyCode::Char('i'),
+            KeyCode::Esc,
+            KeyCode::Esc,
+            KeyCode::Enter,
+        ]
+        .iter()
+        .map(|&code| KeyEvent::from(code))
+        .collect(),
+    );
+    let result = QueryBuilder::default()
+        .input()
+        .vi_mode()
+        .show_with(&mut VirtualScreen::new(20, 4), &mut events);
+    assert_eq!(result.unwrap(), "hi");
+}
@@ -0,0 +1,45 @@
+use std::{
+    env,
+    io::{self, Write},
+    process::{Command, Stdio},
+};
+
+use muroba::query::{Query, QueryBuilder};
+
+const CHILD: &str = "MUROBA_TEST_CHILD";
+
+/// Runs the test `name` again in a child process whose stdin is `input`,
+/// and returns what it printed.
+fn run_with_stdin(name: &str, input: &str) -> String {
+    let mut child = Command::new(env::current_exe().unwrap())
+        .args([name, "--exact", "--nocapture"])
+        .env(CHILD, "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn select_reads_number_from_one() {
+    if env::var_os(CHILD).is_some() {
+        let selected = QueryBuilder::default()
+            .select(&["a", "b", "c"])
+            .show_on(&mut io::sink());
+        println!("selected {:?}", selected.copied());
+        return;
+    }
+    let output = run_with_stdin("select_reads_number_from_one", "1\n");
+    assert!(output.contains("selected Ok(\"a\")"), "{}", output);
+    let output = run_with_stdin("select_reads_number_from_one", "0\n");
+    assert!(output.contains("selected Err(Invalid("), "{}", output);
+}
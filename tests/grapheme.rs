@@ -0,0 +1,110 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use muroba::{
+    complete::Completion,
+    query::{CharacterShield, MultiLineReader, PlainReader, SecretReader, TextReader, ViReader},
+    util::wrap,
+};
+
+fn press(reader: &mut impl TextReader, code: KeyCode) {
+    reader.on_key(&KeyEvent::from(code));
+}
+
+fn type_str(reader: &mut impl TextReader, text: &str) {
+    for c in text.chars() {
+        press(reader, KeyCode::Char(c));
+    }
+}
+
+#[test]
+fn backspace_removes_combining_mark_with_its_base() {
+    let mut reader = PlainReader::default();
+    type_str(&mut reader, "cafe\u{301}");
+    press(&mut reader, KeyCode::Backspace);
+    assert_eq!(reader.text(), "caf");
+}
+
+#[test]
+fn cursor_moves_over_zwj_sequence() {
+    let mut reader = PlainReader::with_text("a👨‍👩‍👧b");
+    press(&mut reader, KeyCode::Left);
+    press(&mut reader, KeyCode::Left);
+    assert_eq!(reader.cursor(), 1);
+    press(&mut reader, KeyCode::Delete);
+    assert_eq!(reader.text(), "ab");
+}
+
+#[test]
+fn backspace_removes_whole_flag() {
+    let mut reader = PlainReader::with_text("🇰🇷🇯🇵");
+    press(&mut reader, KeyCode::Backspace);
+    assert_eq!(reader.text(), "🇰🇷");
+}
+
+#[test]
+fn cjk_is_edited_by_character() {
+    let mut reader = PlainReader::with_text("한국어");
+    press(&mut reader, KeyCode::Left);
+    press(&mut reader, KeyCode::Backspace);
+    assert_eq!(reader.text(), "한어");
+    assert_eq!(reader.cursor(), "한".len());
+}
+
+#[test]
+fn vi_deletes_grapheme_cluster() {
+    let mut reader = ViReader::with_text("e\u{301}x");
+    press(&mut reader, KeyCode::Esc);
+    type_str(&mut reader, "0x");
+    assert_eq!(reader.text(), "x");
+}
+
+#[test]
+fn secret_masks_each_grapheme_cluster() {
+    let mut reader = SecretReader::new(CharacterShield::new('*'));
+    reader.on_paste("👍🏽e\u{301}");
+    assert_eq!(reader.text(), "**");
+    assert_eq!(reader.cursor(), 2);
+    press(&mut reader, KeyCode::Backspace);
+    assert_eq!(reader.input(), "👍🏽");
+}
+
+#[test]
+fn multiline_keeps_display_column_across_cjk() {
+    let mut reader = MultiLineReader::with_text("한국\nabcd");
+    press(&mut reader, KeyCode::Up);
+    assert_eq!(reader.cursor(), "한국".len());
+    press(&mut reader, KeyCode::Left);
+    press(&mut reader, KeyCode::Down);
+    assert_eq!(reader.cursor(), "한국\nab".len());
+}
+
+#[test]
+fn wrap_measures_wide_characters() {
+    assert_eq!(
+        wrap("한국어abc", 4),
+        vec![(0, "한국"), (6, "어ab"), (11, "c")]
+    );
+}
+
+#[test]
+fn wrap_keeps_grapheme_clusters_together() {
+    assert_eq!(wrap("👨‍👩‍👧👨‍👩‍👧", 2), vec![(0, "👨‍👩‍👧"), ("👨‍👩‍👧".len(), "👨‍👩‍👧")]);
+    assert_eq!(
+        wrap("e\u{301}e\u{301}", 1),
+        vec![(0, "e\u{301}"), (3, "e\u{301}")]
+    );
+}
+
+#[test]
+fn common_prefix_does_not_split_grapheme_cluster() {
+    let completion = Completion {
+        start: 0,
+        candidates: vec!["e\u{301}a".into(), "e\u{301}b".into()],
+    };
+    assert_eq!(completion.common_prefix(), "e\u{301}");
+
+    let completion = Completion {
+        start: 0,
+        candidates: vec!["ea".into(), "e\u{301}".into()],
+    };
+    assert_eq!(completion.common_prefix(), "");
+}
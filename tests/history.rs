@@ -0,0 +1,40 @@
+use muroba::history::{FileHistory, History, MemoryHistory};
+
+#[test]
+fn file_history_keeps_special_characters() {
+    let dir = tempfile::tempdir().unwrap();
+    let history = FileHistory::new(dir.path().join("history"));
+    let entry = "tab\there\nnew line\\n not escaped\r";
+    history.push("key\twith tab", entry, 10).unwrap();
+    history.push("other", "plain", 10).unwrap();
+    assert_eq!(history.entries("key\twith tab").unwrap(), vec![entry]);
+    assert_eq!(history.entries("other").unwrap(), vec!["plain"]);
+}
+
+#[test]
+fn history_moves_repeated_entry_to_newest() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = FileHistory::new(dir.path().join("history"));
+    let memory = MemoryHistory::default();
+    for history in [&file as &dyn History, &memory] {
+        for entry in ["a", "b", "a"] {
+            history.push("key", entry, 10).unwrap();
+        }
+        assert_eq!(history.entries("key").unwrap(), vec!["b", "a"]);
+    }
+}
+
+#[test]
+fn history_drops_oldest_entries_over_max_len() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = FileHistory::new(dir.path().join("history"));
+    let memory = MemoryHistory::default();
+    for history in [&file as &dyn History, &memory] {
+        history.push("other", "kept", 2).unwrap();
+        for entry in ["a", "b", "c"] {
+            history.push("key", entry, 2).unwrap();
+        }
+        assert_eq!(history.entries("key").unwrap(), vec!["b", "c"]);
+        assert_eq!(history.entries("other").unwrap(), vec!["kept"]);
+    }
+}
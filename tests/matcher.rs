@@ -0,0 +1,37 @@
+use muroba::{
+    complete::{Completer, MatchCompleter},
+    matcher::{FuzzyMatcher, Matcher, PrefixMatcher, RegexMatcher, SubstringMatcher},
+};
+
+#[test]
+fn fuzzy_prefers_word_starts() {
+    let start = FuzzyMatcher.matches("fb", "foo_bar").unwrap();
+    let middle = FuzzyMatcher.matches("fb", "fooxbar").unwrap();
+    assert_eq!(start.indices, vec![0, 4]);
+    assert!(start.score > middle.score);
+    assert!(FuzzyMatcher.matches("Fb", "foo_bar").is_none());
+}
+
+#[test]
+fn substring_ignores_case() {
+    let found = SubstringMatcher.matches("bar", "FooBar").unwrap();
+    assert_eq!(found.indices, vec![3, 4, 5]);
+    assert!(PrefixMatcher.matches("bar", "FooBar").is_none());
+}
+
+#[test]
+fn regex_counts_characters() {
+    let matcher = RegexMatcher::new();
+    let found = matcher.matches("b.r", "한 bar").unwrap();
+    assert_eq!(found.indices, vec![2, 3, 4]);
+    // An unfinished pattern is matched literally.
+    assert_eq!(matcher.matches("a(", "ca(t").unwrap().indices, vec![1, 2]);
+}
+
+#[test]
+fn completer_ranks_candidates() {
+    let completer = MatchCompleter::new(["checkout", "cherry-pick", "commit"], FuzzyMatcher);
+    let completion = completer.complete("git co", 6);
+    assert_eq!(completion.start, 4);
+    assert_eq!(completion.candidates, vec!["commit", "checkout"]);
+}
@@ -0,0 +1,160 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use muroba::query::{EmptyShield, PlainReader, SecretReader, TextReader, ViReader};
+
+fn press(reader: &mut impl TextReader, code: KeyCode) {
+    reader.on_key(&KeyEvent::from(code));
+}
+
+fn ctrl(reader: &mut impl TextReader, c: char) {
+    reader.on_key(&KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL));
+}
+
+fn alt(reader: &mut impl TextReader, c: char) {
+    reader.on_key(&KeyEvent::new(KeyCode::Char(c), KeyModifiers::ALT));
+}
+
+fn type_str(reader: &mut impl TextReader, text: &str) {
+    for c in text.chars() {
+        press(reader, KeyCode::Char(c));
+    }
+}
+
+#[test]
+fn vi_caps_huge_count() {
+    let mut reader = ViReader::with_text("hello world");
+    press(&mut reader, KeyCode::Esc);
+    press(&mut reader, KeyCode::Char('0'));
+    type_str(&mut reader, &"9".repeat(25));
+    press(&mut reader, KeyCode::Char('l'));
+    assert_eq!(reader.cursor(), 10);
+    press(&mut reader, KeyCode::Char('0'));
+    type_str(&mut reader, &"9".repeat(25));
+    press(&mut reader, KeyCode::Char('x'));
+    assert_eq!(reader.text(), "");
+}
+
+#[test]
+fn undo_merges_typing_and_deletion() {
+    let mut reader = PlainReader::with_text("");
+    type_str(&mut reader, "hello");
+    press(&mut reader, KeyCode::Backspace);
+    press(&mut reader, KeyCode::Backspace);
+    assert_eq!(reader.text(), "hel");
+    ctrl(&mut reader, 'z');
+    assert_eq!(reader.text(), "hello");
+    ctrl(&mut reader, 'z');
+    assert_eq!(reader.text(), "");
+}
+
+#[test]
+fn undo_separates_kills() {
+    let mut reader = PlainReader::with_text("");
+    type_str(&mut reader, "foo bar");
+    ctrl(&mut reader, 'w');
+    ctrl(&mut reader, 'u');
+    assert_eq!(reader.text(), "");
+    ctrl(&mut reader, 'z');
+    assert_eq!(reader.text(), "foo ");
+    ctrl(&mut reader, 'z');
+    assert_eq!(reader.text(), "foo bar");
+}
+
+#[test]
+fn new_edit_clears_redo() {
+    let mut reader = PlainReader::with_text("");
+    type_str(&mut reader, "abc");
+    ctrl(&mut reader, 'z');
+    ctrl(&mut reader, 'Z');
+    assert_eq!(reader.text(), "abc");
+    ctrl(&mut reader, 'z');
+    type_str(&mut reader, "x");
+    ctrl(&mut reader, 'Z');
+    assert_eq!(reader.text(), "x");
+}
+
+#[test]
+fn vi_undoes_insert_mode_at_once() {
+    let mut reader = ViReader::with_text("");
+    type_str(&mut reader, "ab");
+    press(&mut reader, KeyCode::Left);
+    type_str(&mut reader, "c");
+    press(&mut reader, KeyCode::Esc);
+    assert_eq!(reader.text(), "acb");
+    press(&mut reader, KeyCode::Char('u'));
+    assert_eq!(reader.text(), "");
+}
+
+#[test]
+fn vi_keeps_insert_group_after_undo() {
+    let mut reader = ViReader::with_text("");
+    type_str(&mut reader, "ab");
+    ctrl(&mut reader, 'z');
+    assert_eq!(reader.text(), "");
+    type_str(&mut reader, "cd");
+    press(&mut reader, KeyCode::Left);
+    type_str(&mut reader, "e");
+    press(&mut reader, KeyCode::Esc);
+    assert_eq!(reader.text(), "ced");
+    press(&mut reader, KeyCode::Char('u'));
+    assert_eq!(reader.text(), "");
+}
+
+#[test]
+fn kills_words_and_yanks_them_back() {
+    let mut reader = PlainReader::with_text("foo bar baz");
+    ctrl(&mut reader, 'w');
+    ctrl(&mut reader, 'w');
+    assert_eq!(reader.text(), "foo ");
+    ctrl(&mut reader, 'y');
+    assert_eq!(reader.text(), "foo bar ");
+    alt(&mut reader, 'y');
+    assert_eq!(reader.text(), "foo baz");
+    alt(&mut reader, 'y');
+    assert_eq!(reader.text(), "foo bar ");
+}
+
+#[test]
+fn kills_to_line_start_and_end() {
+    let mut reader = PlainReader::with_text("hello world");
+    alt(&mut reader, 'b');
+    ctrl(&mut reader, 'k');
+    assert_eq!(reader.text(), "hello ");
+    ctrl(&mut reader, 'y');
+    assert_eq!(reader.text(), "hello world");
+    ctrl(&mut reader, 'a');
+    alt(&mut reader, 'f');
+    ctrl(&mut reader, 'u');
+    assert_eq!(reader.text(), " world");
+    assert_eq!(reader.cursor(), 0);
+}
+
+#[test]
+fn moves_over_unicode_words() {
+    let mut reader = PlainReader::with_text("héllo wörld 한글");
+    alt(&mut reader, 'b');
+    assert_eq!(&reader.text()[reader.cursor()..], "한글");
+    alt(&mut reader, 'b');
+    assert_eq!(&reader.text()[reader.cursor()..], "wörld 한글");
+    alt(&mut reader, 'f');
+    assert_eq!(&reader.text()[reader.cursor()..], " 한글");
+    ctrl(&mut reader, 'w');
+    assert_eq!(reader.text(), "héllo  한글");
+}
+
+#[test]
+fn each_reader_has_own_kill_ring() {
+    let mut plain = PlainReader::with_text("visible");
+    ctrl(&mut plain, 'u');
+
+    let mut secret = SecretReader::new(EmptyShield);
+    ctrl(&mut secret, 'y');
+    assert_eq!(secret.input(), "");
+    type_str(&mut secret, "secret");
+    ctrl(&mut secret, 'u');
+    assert_eq!(secret.input(), "");
+    ctrl(&mut secret, 'y');
+    assert_eq!(secret.input(), "secret");
+
+    ctrl(&mut plain, 'y');
+    assert_eq!(plain.text(), "visible");
+}
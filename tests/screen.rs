@@ -0,0 +1,357 @@
+use std::io::Write;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use muroba::{
+    complete::{Completion, MatchCompleter},
+    event::ScriptedEvents,
+    matcher::FuzzyMatcher,
+    query::{Query, QueryBuilder},
+    screen::VirtualScreen,
+    terminal::Backend,
+};
+
+fn keys(codes: &[KeyCode]) -> ScriptedEvents {
+    ScriptedEvents::new(codes.iter().map(|&code| KeyEvent::from(code)).collect())
+}
+
+fn dyn_list(filter: String) -> Vec<&'static str> {
+    ["apple", "banana", "blueberry", "cherry"]
+        .iter()
+        .copied()
+        .filter(|item| item.contains(filter.as_str()))
+        .collect()
+}
+
+#[test]
+fn select_draws_cursor() {
+    let mut screen = VirtualScreen::new(20, 6);
+    let mut events = keys(&[KeyCode::Down]);
+    let _ = QueryBuilder::default()
+        .with_prompt("Fruit")
+        .select(&["apple", "banana", "cherry"])
+        .show_with(&mut screen, &mut events);
+    assert_eq!(screen.contents(), "? Fruit\n  apple\n> banana\n  cherry");
+}
+
+#[test]
+fn select_leaves_answer() {
+    let mut screen = VirtualScreen::new(20, 6);
+    let mut events = keys(&[KeyCode::Down, KeyCode::Enter]);
+    let result = QueryBuilder::default()
+        .with_prompt("Fruit")
+        .select(&["apple", "banana", "cherry"])
+        .show_with(&mut screen, &mut events)
+        .unwrap();
+    assert_eq!(*result, "banana");
+    assert_eq!(screen.contents(), "? Fruit > banana");
+    assert_eq!(screen.cursor_position().unwrap(), (0, 1));
+    assert!(screen.is_cursor_visible());
+}
+
+#[test]
+fn select_many_marks_selected_items() {
+    let mut screen = VirtualScreen::new(20, 6);
+    let mut events = keys(&[KeyCode::Char(' '), KeyCode::Down, KeyCode::Down]);
+    let _ = QueryBuilder::default()
+        .with_prompt("Fruit")
+        .select(&["apple", "banana", "cherry"])
+        .many()
+        .show_with(&mut screen, &mut events);
+    assert_eq!(screen.contents(), "? Fruit\n✓ apple\n  banana\n> cherry");
+}
+
+#[test]
+fn select_with_fixed_rows_scrolls() {
+    let mut screen = VirtualScreen::new(20, 6);
+    let mut events = keys(&[KeyCode::Up]);
+    let _ = QueryBuilder::default()
+        .with_prompt("Fruit")
+        .select(&["apple", "banana", "cherry", "durian"])
+        .fix_rows(3)
+        .show_with(&mut screen, &mut events);
+    assert_eq!(screen.contents(), "? Fruit\n  cherry\n> durian\n  apple");
+}
+
+#[test]
+fn select_trims_long_items() {
+    let mut screen = VirtualScreen::new(10, 4);
+    let mut events = keys(&[]);
+    let _ = QueryBuilder::default()
+        .with_prompt("Pick")
+        .select(&["abcdefghijkl"])
+        .show_with(&mut screen, &mut events);
+    assert_eq!(screen.contents(), "? Pick\n> …fghijkl");
+}
+
+#[test]
+fn dyn_select_draws_filtered_list() {
+    let mut screen = VirtualScreen::new(20, 6);
+    let mut events = keys(&[KeyCode::Char('b'), KeyCode::Down]);
+    let _ = QueryBuilder::default()
+        .with_prompt("Fruit")
+        .dyn_select(dyn_list)
+        .show_with(&mut screen, &mut events);
+    assert_eq!(screen.contents(), "? Fruit > b\n  banana\n> blueberry");
+    assert_eq!(screen.cursor_position().unwrap(), (11, 0));
+}
+
+#[test]
+fn dyn_select_leaves_answer() {
+    let mut screen = VirtualScreen::new(20, 6);
+    let mut events = keys(&[KeyCode::Char('r'), KeyCode::Down, KeyCode::Enter]);
+    let result = QueryBuilder::default()
+        .with_prompt("Fruit")
+        .dyn_select(dyn_list)
+        .show_with(&mut screen, &mut events)
+        .unwrap();
+    assert_eq!(result, Some("cherry"));
+    assert_eq!(screen.contents(), "? Fruit > cherry");
+}
+
+#[test]
+fn screen_places_wide_and_combining_characters() {
+    let mut screen = VirtualScreen::new(6, 3);
+    write!(screen, "한e\u{301}x\r\n12345678").unwrap();
+    assert_eq!(screen.rows(), vec!["한e\u{301}x", "123456", "78"]);
+    write!(screen, "\x1b[2A\x1b[2G\x1b[K").unwrap();
+    assert_eq!(screen.rows(), vec!["", "123456", "78"]);
+}
+
+#[test]
+fn confirm_echoes_answer_on_screen() {
+    let mut screen = VirtualScreen::new(20, 3);
+    let mut events = keys(&[KeyCode::Enter]);
+    let result = QueryBuilder::default()
+        .with_prompt("Go")
+        .confirm(Some(false))
+        .show_with(&mut screen, &mut events);
+    assert!(matches!(result, Ok(false)));
+    assert_eq!(screen.contents(), "? Go [y/N] > N");
+    assert_eq!(screen.cursor_position().unwrap(), (0, 1));
+}
+
+#[test]
+fn select_many_returns_owned_values() {
+    #[derive(Debug, PartialEq)]
+    struct Fruit(&'static str, u32);
+
+    impl std::fmt::Display for Fruit {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{} ({})", self.0, self.1)
+        }
+    }
+
+    let mut screen = VirtualScreen::new(20, 6);
+    let mut events = keys(&[KeyCode::Down, KeyCode::Char(' '), KeyCode::Enter]);
+    let result = QueryBuilder::default()
+        .with_prompt("Fruit")
+        .select(vec![Fruit("apple", 3), Fruit("banana", 5)])
+        .many()
+        .show_with(&mut screen, &mut events)
+        .unwrap();
+    assert_eq!(result, vec![Fruit("banana", 5)]);
+}
+
+#[test]
+fn filterable_select_ranks_matches() {
+    let mut screen = VirtualScreen::new(20, 6);
+    let mut events = keys(&[KeyCode::Char('p')]);
+    let _ = QueryBuilder::default()
+        .with_prompt("Fruit")
+        .select(&["grape", "banana", "apple", "peach"])
+        .filterable()
+        .show_with(&mut screen, &mut events);
+    assert_eq!(screen.contents(), "? Fruit > p\n> peach\n  grape\n  apple");
+    assert_eq!(screen.cursor_position().unwrap(), (11, 0));
+}
+
+#[test]
+fn filterable_select_keeps_hidden_selection() {
+    let mut screen = VirtualScreen::new(20, 6);
+    let mut events = keys(&[
+        KeyCode::Tab,
+        KeyCode::Char('c'),
+        KeyCode::Tab,
+        KeyCode::Enter,
+    ]);
+    let result = QueryBuilder::default()
+        .with_prompt("Fruit")
+        .select(&["apple", "banana", "cherry"])
+        .many()
+        .filterable()
+        .show_with(&mut screen, &mut events)
+        .unwrap();
+    assert_eq!(result, vec![&"apple", &"cherry"]);
+    assert_eq!(screen.contents(), "? Fruit");
+}
+
+#[test]
+fn select_jumps_to_typed_letter() {
+    let mut screen = VirtualScreen::new(20, 6);
+    let mut events = keys(&[KeyCode::Char('b'), KeyCode::Char('B')]);
+    let _ = QueryBuilder::default()
+        .with_prompt("Fruit")
+        .select(&["apple", "banana", "blueberry", "cherry"])
+        .show_with(&mut screen, &mut events);
+    assert_eq!(
+        screen.contents(),
+        "? Fruit\n  apple\n  banana\n> blueberry\n  cherry"
+    );
+}
+
+#[test]
+fn select_pages_through_fixed_rows() {
+    let mut screen = VirtualScreen::new(20, 6);
+    let mut events = keys(&[KeyCode::PageDown, KeyCode::PageDown]);
+    let _ = QueryBuilder::default()
+        .with_prompt("Fruit")
+        .select(&["apple", "banana", "cherry", "durian", "elderberry"])
+        .fix_rows(2)
+        .show_with(&mut screen, &mut events);
+    assert_eq!(screen.contents(), "? Fruit\n> elderberry\n  apple");
+}
+
+#[test]
+fn select_picks_shown_row_by_number() {
+    let mut screen = VirtualScreen::new(20, 6);
+    let mut events = keys(&[KeyCode::Char('1')]);
+    let result = QueryBuilder::default()
+        .with_prompt("Fruit")
+        .select(&["apple", "banana", "cherry", "durian"])
+        .fix_rows(3)
+        .show_with(&mut screen, &mut events)
+        .unwrap();
+    assert_eq!(*result, "durian");
+    assert_eq!(screen.contents(), "? Fruit > durian");
+}
+
+#[test]
+fn rejected_default_is_not_put_into_input() {
+    let mut screen = VirtualScreen::new(40, 4);
+    let mut events = keys(&[KeyCode::Enter, KeyCode::Char('5'), KeyCode::Enter]);
+    let result = QueryBuilder::default()
+        .with_prompt("Age")
+        .input()
+        .with_default("abc")
+        .parse::<u32>()
+        .show_with(&mut screen, &mut events)
+        .unwrap();
+    assert_eq!(result, 5);
+}
+
+#[test]
+fn fuzzy_completion_keeps_typed_word() {
+    let mut screen = VirtualScreen::new(30, 6);
+    let mut events = keys(&[KeyCode::Char('f'), KeyCode::Char('b'), KeyCode::Tab]);
+    let _ = QueryBuilder::default()
+        .with_prompt("Word")
+        .input()
+        .with_completer(MatchCompleter::new(["football", "foobar"], FuzzyMatcher))
+        .show_with(&mut screen, &mut events);
+    assert_eq!(screen.contents(), "? Word > fb\n  foobar\n  football");
+}
+
+#[test]
+fn screen_resizes_around_cursor() {
+    let mut screen = VirtualScreen::new(6, 3);
+    write!(screen, "ab한\r\n12\r\nxyz").unwrap();
+    screen.resize(3, 2);
+    assert_eq!(screen.rows(), vec!["12", "xyz"]);
+    assert_eq!(screen.cursor_position().unwrap(), (2, 1));
+    screen.resize(4, 3);
+    write!(screen, "\r\n한").unwrap();
+    assert_eq!(screen.rows(), vec!["12", "xyz", "한"]);
+}
+
+#[test]
+fn validation_error_stays_until_edit() {
+    let validate = |text: &str| match text.len() {
+        0..=2 => Err("too short".to_string()),
+        _ => Ok(()),
+    };
+    let mut screen = VirtualScreen::new(30, 4);
+    let mut events = keys(&[KeyCode::Char('a'), KeyCode::Enter, KeyCode::Left]);
+    let _ = QueryBuilder::default()
+        .with_prompt("Name")
+        .input()
+        .with_validator(validate)
+        .show_with(&mut screen, &mut events);
+    assert_eq!(screen.contents(), "? Name > a\n✗ too short");
+
+    let mut screen = VirtualScreen::new(30, 4);
+    let mut events = keys(&[KeyCode::Char('a'), KeyCode::Enter, KeyCode::Char('b')]);
+    let _ = QueryBuilder::default()
+        .with_prompt("Name")
+        .input()
+        .with_validator(validate)
+        .show_with(&mut screen, &mut events);
+    assert_eq!(screen.contents(), "? Name > ab");
+}
+
+#[test]
+fn placeholder_shows_only_on_empty_input() {
+    let mut screen = VirtualScreen::new(30, 4);
+    let _ = QueryBuilder::default()
+        .with_prompt("Name")
+        .input()
+        .with_placeholder("John Doe")
+        .show_with(&mut screen, &mut keys(&[]));
+    assert_eq!(screen.contents(), "? Name > John Doe");
+    assert_eq!(screen.cursor_position().unwrap(), (9, 0));
+
+    let mut screen = VirtualScreen::new(30, 4);
+    let _ = QueryBuilder::default()
+        .with_prompt("Name")
+        .input()
+        .with_placeholder("John Doe")
+        .show_with(&mut screen, &mut keys(&[KeyCode::Char('a')]));
+    assert_eq!(screen.contents(), "? Name > a");
+}
+
+#[test]
+fn suggestion_is_drawn_after_cursor_and_accepted() {
+    let mut screen = VirtualScreen::new(30, 4);
+    let mut events = keys(&[KeyCode::Char('h'), KeyCode::Char('e')]);
+    let _ = QueryBuilder::default()
+        .with_prompt("Say")
+        .input()
+        .with_suggester(vec!["hello"])
+        .show_with(&mut screen, &mut events);
+    assert_eq!(screen.contents(), "? Say > hello");
+    assert_eq!(screen.cursor_position().unwrap(), (10, 0));
+
+    let mut screen = VirtualScreen::new(30, 4);
+    let mut events = keys(&[KeyCode::Char('h'), KeyCode::Right, KeyCode::Enter]);
+    let result = QueryBuilder::default()
+        .with_prompt("Say")
+        .input()
+        .with_suggester(vec!["hello"])
+        .show_with(&mut screen, &mut events)
+        .unwrap();
+    assert_eq!(result, "hello");
+}
+
+#[test]
+fn second_tab_lists_candidates() {
+    let complete = |text: &str, cursor: usize| Completion {
+        start: text[..cursor].rfind(' ').map_or(0, |space| space + 1),
+        candidates: vec!["checkout".to_string(), "cherry-pick".to_string()],
+    };
+    let mut screen = VirtualScreen::new(30, 6);
+    let mut events = keys(&[KeyCode::Char('c'), KeyCode::Tab]);
+    let _ = QueryBuilder::default()
+        .with_prompt("Git")
+        .input()
+        .with_completer(complete)
+        .show_with(&mut screen, &mut events);
+    assert_eq!(screen.contents(), "? Git > che");
+
+    let mut screen = VirtualScreen::new(30, 6);
+    let mut events = keys(&[KeyCode::Char('c'), KeyCode::Tab, KeyCode::Tab]);
+    let _ = QueryBuilder::default()
+        .with_prompt("Git")
+        .input()
+        .with_completer(complete)
+        .show_with(&mut screen, &mut events);
+    assert_eq!(screen.contents(), "? Git > che\n  checkout\n  cherry-pick");
+}